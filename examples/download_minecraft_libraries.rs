@@ -0,0 +1,49 @@
+use piston_mc::manifest_v2::ManifestV2;
+use piston_mc::version_manifest::VersionManifest;
+use simple_download_utility::MultiDownloadProgress;
+
+#[tokio::main]
+async fn main() {
+    // Record the start time to measure total execution duration
+    let start_time = std::time::Instant::now();
+
+    // Fetch the Minecraft version manifest from Mojang's Piston API
+    let manifest = ManifestV2::fetch().await.expect("Failed to fetch manifest.");
+
+    // Attempt to retrieve the specific version manifest for Minecraft 1.12.2
+    let version: anyhow::Result<Option<VersionManifest>> = manifest.version("1.12.2").await;
+
+    // Proceed only if the version was found successfully
+    if let Ok(Some(version)) = version {
+        // Configure the maximum number of concurrent download tasks
+        let number_of_parallel_downloads = 20;
+
+        // Create a channel for receiving download progress updates
+        // Buffer size of 16 allows some backpressure handling between producer and consumer
+        let (sender, mut receiver) = tokio::sync::mpsc::channel::<MultiDownloadProgress>(16);
+
+        // Start downloading the libraries allowed on this platform into the maven layout
+        let task = version.download_libraries("target/examples/libraries", number_of_parallel_downloads, Some(sender));
+
+        // Spawn a separate async task to handle progress updates without blocking the download
+        tokio::spawn(async move {
+            // Continuously receive and display progress until the channel closes
+            while let Some(progress) = receiver.recv().await {
+                // Convert download speed from bytes/sec to megabytes/sec for readability
+                let mb_per_sec = progress.bytes_per_second as f32 / 1024.0 / 1024.0;
+
+                // Calculate download completion percentage based on file count
+                let percent = (progress.files_downloaded as f32 / progress.files_total as f32) * 100.0;
+
+                // Display formatted progress: percentage, file counts, and transfer speed
+                println!("Download progress: {:.2}% ({}/{}) {:.2} MB/s", percent, progress.files_downloaded, progress.files_total, mb_per_sec);
+            }
+        });
+
+        // Await the download task completion and handle any errors
+        task.await.expect("Failed to download libraries.");
+    }
+
+    // Display the total execution time for benchmarking purposes
+    println!("Time elapsed: {:?}", start_time.elapsed());
+}
@@ -1,4 +1,4 @@
-use piston_mc::java::JavaManifest;
+use piston_mc::java::{JavaManifest, Platform};
 
 #[tokio::main]
 async fn main() {
@@ -7,10 +7,12 @@ async fn main() {
 
     // Get the gamma runtime (Java 17) for Windows x64
     // You can change this to match your platform:
-    // - manifest.linux.gamma for Linux x64
-    // - manifest.macos.gamma for macOS x64
-    // - manifest.macos_arm64.gamma for macOS ARM64 (Apple Silicon)
-    let runtime = manifest.windows_x64.gamma.first().expect("No gamma runtime available.");
+    // - Platform::Linux for Linux x64
+    // - Platform::MacOs for macOS x64
+    // - Platform::MacOsArm64 for macOS ARM64 (Apple Silicon)
+    // - Platform::current() for the platform this example runs on
+    let runtimes = manifest.runtimes(Platform::WindowsX64).expect("No runtimes available for Windows x64.");
+    let runtime = runtimes.gamma().first().expect("No gamma runtime available.");
 
     // Display the runtime version information
     println!("Java Runtime: {}", runtime);
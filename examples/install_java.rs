@@ -1,6 +1,7 @@
-use piston_mc::java::JavaManifest;
-use std::path::Path;
+use piston_mc::java::{JavaManifest, Platform};
+use piston_mc::version_manifest::JavaVersion;
 use simple_download_utility::MultiDownloadProgress;
+use std::path::Path;
 
 #[tokio::main]
 async fn main() {
@@ -10,12 +11,13 @@ async fn main() {
     // Fetch the Java runtime manifest from Mojang's Piston API
     let manifest = JavaManifest::fetch().await.expect("Failed to fetch Java manifest.");
 
-    // Get the gamma runtime (Java 17) for Windows x64
-    // You can change this to match your platform:
-    // - manifest.linux.gamma for Linux x64
-    // - manifest.macos.gamma for macOS x64
-    // - manifest.macos_arm64.gamma for macOS ARM64 (Apple Silicon)
-    let runtime = manifest.windows_x64.gamma.first().expect("No gamma runtime available.");
+    // Describe the runtime to install, as found in the `javaVersion` of a version manifest
+    // Versions without a `javaVersion` use `JavaVersion::default()`, the legacy Java 8 runtime
+    let java_version = JavaVersion { component: "java-runtime-gamma".to_string(), major_version: 17 };
+
+    // Get the gamma runtime (Java 17) for the platform this example runs on
+    let platform = Platform::current().expect("No java runtimes are published for this platform.");
+    let runtime = manifest.runtime_for(&java_version, platform).expect("No gamma runtime available.");
 
     // Display the runtime version being installed
     println!("Installing Java Runtime: {}", runtime);
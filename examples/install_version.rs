@@ -0,0 +1,40 @@
+use piston_mc::game_directory::GameDirectory;
+use piston_mc::installer::Installer;
+use simple_download_utility::MultiDownloadProgress;
+
+#[tokio::main]
+async fn main() {
+    // Record the start time to measure total execution duration
+    let start_time = std::time::Instant::now();
+
+    // Create a channel for receiving download progress updates
+    // Buffer size of 16 allows some backpressure handling between producer and consumer
+    let (sender, mut receiver) = tokio::sync::mpsc::channel::<MultiDownloadProgress>(16);
+
+    // Install the client jar, libraries, assets, logging configuration and java runtime of 1.20.1
+    // Running this again only downloads the files that are missing or corrupted
+    let installer = Installer::new("1.20.1", GameDirectory::new("target/examples/minecraft")).parallel(20);
+    let task = installer.install(Some(sender));
+
+    // Spawn a separate async task to handle progress updates without blocking the installation
+    tokio::spawn(async move {
+        // Continuously receive and display progress until the channel closes
+        while let Some(progress) = receiver.recv().await {
+            // Convert download speed from bytes/sec to megabytes/sec for readability
+            let mb_per_sec = progress.bytes_per_second as f32 / 1024.0 / 1024.0;
+
+            // Calculate download completion percentage based on file count
+            let percent = (progress.files_downloaded as f32 / progress.files_total as f32) * 100.0;
+
+            // Display formatted progress: percentage, file counts, and transfer speed
+            println!("Install progress: {:.2}% ({}/{}) {:.2} MB/s", percent, progress.files_downloaded, progress.files_total, mb_per_sec);
+        }
+    });
+
+    // Await the installation and handle any errors
+    let installation = task.await.expect("Failed to install version.");
+    println!("Installed {} with java in {:?}", installation.version.id, installation.java_directory);
+
+    // Display the total execution time for benchmarking purposes
+    println!("Time elapsed: {:?}", start_time.elapsed());
+}
@@ -0,0 +1,313 @@
+//! Resolution of the `arguments` / `minecraftArguments` templates of a [`VersionManifest`].
+//!
+//! Both argument formats are normalised into [`Post113`] first, so pre-1.13 versions get the
+//! same JVM defaults and feature dependent game arguments the official launcher adds for them.
+//! Placeholders such as `${auth_player_name}` are then filled in from an [`ArgumentContext`];
+//! placeholders the context does not know about are left untouched.
+//!
+//! # Example
+//! ```no_run
+//! use piston_mc::arguments::ArgumentContext;
+//! use piston_mc::manifest_v2::ManifestV2;
+//! use piston_mc::rules::RuleEnvironment;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let manifest = ManifestV2::fetch().await.unwrap();
+//!     let version = manifest.version("1.20.1").await.unwrap().unwrap();
+//!     let context = ArgumentContext { auth_player_name: "Steve".to_string(), ..Default::default() };
+//!     let environment = RuleEnvironment::current();
+//!     println!("{:?}", version.jvm_arguments(&context, &environment));
+//!     println!("{:?}", version.game_arguments(&context, &environment));
+//! }
+//! ```
+
+use crate::rules::{FEATURE_HAS_CUSTOM_RESOLUTION, FEATURE_IS_DEMO_USER, RuleEnvironment};
+use crate::version_manifest::{ArgumentValue, Arguments, ConditionalArgument, GameArgument, OsRule, Post113, Rule, RuleAction, VersionManifest};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Values substituted into the `${...}` placeholders of the launch arguments.
+#[derive(Clone, Debug)]
+pub struct ArgumentContext {
+    pub auth_player_name: String,
+    pub auth_uuid: String,
+    pub auth_access_token: String,
+    pub auth_xuid: String,
+    pub client_id: String,
+    /// `msa` for Microsoft accounts, `legacy` or `mojang` for old account types.
+    pub user_type: String,
+    /// JSON object of the user's properties; only used by very old versions.
+    pub user_properties: String,
+    pub version_name: String,
+    pub version_type: String,
+    pub game_directory: PathBuf,
+    pub assets_root: PathBuf,
+    pub assets_index_name: String,
+    /// Directory holding the reconstructed assets of legacy and virtual asset indexes.
+    pub game_assets: PathBuf,
+    pub natives_directory: PathBuf,
+    pub library_directory: PathBuf,
+    pub classpath: String,
+    pub classpath_separator: String,
+    pub launcher_name: String,
+    pub launcher_version: String,
+    pub resolution_width: Option<u32>,
+    pub resolution_height: Option<u32>,
+    pub quick_play_path: Option<String>,
+    pub quick_play_singleplayer: Option<String>,
+    pub quick_play_multiplayer: Option<String>,
+    pub quick_play_realms: Option<String>,
+}
+
+impl Default for ArgumentContext {
+    fn default() -> Self {
+        Self {
+            auth_player_name: String::new(),
+            auth_uuid: String::new(),
+            auth_access_token: String::new(),
+            auth_xuid: String::new(),
+            client_id: String::new(),
+            user_type: "msa".to_string(),
+            user_properties: "{}".to_string(),
+            version_name: String::new(),
+            version_type: String::new(),
+            game_directory: PathBuf::new(),
+            assets_root: PathBuf::new(),
+            assets_index_name: String::new(),
+            game_assets: PathBuf::new(),
+            natives_directory: PathBuf::new(),
+            library_directory: PathBuf::new(),
+            classpath: String::new(),
+            classpath_separator: CLASSPATH_SEPARATOR.to_string(),
+            launcher_name: env!("CARGO_PKG_NAME").to_string(),
+            launcher_version: env!("CARGO_PKG_VERSION").to_string(),
+            resolution_width: None,
+            resolution_height: None,
+            quick_play_path: None,
+            quick_play_singleplayer: None,
+            quick_play_multiplayer: None,
+            quick_play_realms: None,
+        }
+    }
+}
+
+/// The separator the JVM expects between classpath entries on this platform.
+#[cfg(windows)]
+pub const CLASSPATH_SEPARATOR: &str = ";";
+/// The separator the JVM expects between classpath entries on this platform.
+#[cfg(not(windows))]
+pub const CLASSPATH_SEPARATOR: &str = ":";
+
+impl ArgumentContext {
+    /// Returns the value of the placeholder `${key}`, or `None` if it is unknown or unset.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let value = match key {
+            "auth_player_name" => self.auth_player_name.clone(),
+            "auth_uuid" => self.auth_uuid.clone(),
+            "auth_access_token" => self.auth_access_token.clone(),
+            "auth_session" => format!("token:{}:{}", self.auth_access_token, self.auth_uuid),
+            "auth_xuid" => self.auth_xuid.clone(),
+            "clientid" => self.client_id.clone(),
+            "user_type" => self.user_type.clone(),
+            "user_properties" => self.user_properties.clone(),
+            "version_name" => self.version_name.clone(),
+            "version_type" => self.version_type.clone(),
+            "game_directory" => self.game_directory.to_string_lossy().into_owned(),
+            "assets_root" => self.assets_root.to_string_lossy().into_owned(),
+            "assets_index_name" => self.assets_index_name.clone(),
+            "game_assets" => self.game_assets.to_string_lossy().into_owned(),
+            "natives_directory" => self.natives_directory.to_string_lossy().into_owned(),
+            "library_directory" => self.library_directory.to_string_lossy().into_owned(),
+            "classpath" => self.classpath.clone(),
+            "classpath_separator" => self.classpath_separator.clone(),
+            "launcher_name" => self.launcher_name.clone(),
+            "launcher_version" => self.launcher_version.clone(),
+            "resolution_width" => self.resolution_width?.to_string(),
+            "resolution_height" => self.resolution_height?.to_string(),
+            "quickPlayPath" => self.quick_play_path.clone()?,
+            "quickPlaySingleplayer" => self.quick_play_singleplayer.clone()?,
+            "quickPlayMultiplayer" => self.quick_play_multiplayer.clone()?,
+            "quickPlayRealms" => self.quick_play_realms.clone()?,
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Replaces every known `${key}` placeholder in `template`.
+    pub fn substitute(&self, template: &str) -> String {
+        let mut result = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("${") {
+            let Some(length) = rest[start..].find('}') else {
+                break;
+            };
+            let key = &rest[start + 2..start + length];
+            result.push_str(&rest[..start]);
+            match self.get(key) {
+                Some(value) => result.push_str(&value),
+                None => result.push_str(&rest[start..=start + length]),
+            }
+            rest = &rest[start + length + 1..];
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+impl Arguments {
+    /// Normalises both argument formats into the post-1.13 structure.
+    ///
+    /// Pre-1.13 versions only ship game arguments, so this adds the JVM arguments and the
+    /// demo / custom resolution game arguments the official launcher uses for them.
+    pub fn to_post113(&self) -> Post113 {
+        match self {
+            Arguments::Post113(arguments) => arguments.clone(),
+            Arguments::Pre113(arguments) => {
+                let mut game: Vec<GameArgument> = arguments.split_whitespace().map(|argument| GameArgument::Plain(argument.to_string())).collect();
+                game.push(feature_argument(FEATURE_IS_DEMO_USER, ArgumentValue::Single("--demo".to_string())));
+                game.push(feature_argument(
+                    FEATURE_HAS_CUSTOM_RESOLUTION,
+                    ArgumentValue::Multiple(vec![
+                        "--width".to_string(),
+                        "${resolution_width}".to_string(),
+                        "--height".to_string(),
+                        "${resolution_height}".to_string(),
+                    ]),
+                ));
+
+                let jvm = vec![
+                    os_argument("osx", None, ArgumentValue::Single("-XstartOnFirstThread".to_string())),
+                    os_argument(
+                        "windows",
+                        None,
+                        ArgumentValue::Single("-XX:HeapDumpPath=MojangTricksIntelDriversForPerformance_javaw.exe_minecraft.exe.heapdump".to_string()),
+                    ),
+                    os_argument(
+                        "windows",
+                        Some("^10\\."),
+                        ArgumentValue::Multiple(vec!["-Dos.name=Windows 10".to_string(), "-Dos.version=10.0".to_string()]),
+                    ),
+                    GameArgument::Plain("-Djava.library.path=${natives_directory}".to_string()),
+                    GameArgument::Plain("-cp".to_string()),
+                    GameArgument::Plain("${classpath}".to_string()),
+                ];
+
+                Post113 { game, jvm }
+            }
+        }
+    }
+}
+
+impl GameArgument {
+    /// Returns the raw values of this argument, or nothing if its rules disallow it in `environment`.
+    pub fn values(&self, environment: &RuleEnvironment) -> Vec<&str> {
+        match self {
+            GameArgument::Plain(value) => vec![value.as_str()],
+            GameArgument::Conditional(argument) if argument.is_allowed(environment) => match &argument.value {
+                ArgumentValue::Single(value) => vec![value.as_str()],
+                ArgumentValue::Multiple(values) => values.iter().map(String::as_str).collect(),
+            },
+            GameArgument::Conditional(_) => vec![],
+        }
+    }
+}
+
+/// Evaluates the rules of `arguments` and substitutes the placeholders of the remaining values.
+pub fn resolve_arguments(arguments: &[GameArgument], context: &ArgumentContext, environment: &RuleEnvironment) -> Vec<String> {
+    arguments.iter().flat_map(|argument| argument.values(environment)).map(|value| context.substitute(value)).collect()
+}
+
+impl VersionManifest {
+    /// Returns the final JVM arguments of this version, up to but excluding the main class.
+    pub fn jvm_arguments(&self, context: &ArgumentContext, environment: &RuleEnvironment) -> Vec<String> {
+        resolve_arguments(&self.arguments.to_post113().jvm, context, environment)
+    }
+
+    /// Returns the final game arguments of this version, which follow the main class.
+    pub fn game_arguments(&self, context: &ArgumentContext, environment: &RuleEnvironment) -> Vec<String> {
+        resolve_arguments(&self.arguments.to_post113().game, context, environment)
+    }
+}
+
+fn feature_argument(feature: &str, value: ArgumentValue) -> GameArgument {
+    let rule = Rule { action: RuleAction::Allow, features: Some(HashMap::from([(feature.to_string(), true)])), os: None };
+    GameArgument::Conditional(ConditionalArgument { rules: vec![rule], value })
+}
+
+fn os_argument(name: &str, version: Option<&str>, value: ArgumentValue) -> GameArgument {
+    let os = OsRule { name: Some(name.to_string()), arch: None, version: version.map(str::to_string) };
+    let rule = Rule { action: RuleAction::Allow, features: None, os: Some(os) };
+    GameArgument::Conditional(ConditionalArgument { rules: vec![rule], value })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::*;
+    use crate::rules::RuleEnvironment;
+    use crate::version_manifest::Arguments;
+
+    fn context() -> ArgumentContext {
+        ArgumentContext {
+            auth_player_name: "Steve".to_string(),
+            version_name: "1.20.1".to_string(),
+            natives_directory: PathBuf::from("natives"),
+            classpath: "a.jar:b.jar".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn substitute() {
+        let context = context();
+        assert_eq!(context.substitute("--username ${auth_player_name}"), "--username Steve");
+        assert_eq!(context.substitute("-Djava.library.path=${natives_directory}"), "-Djava.library.path=natives");
+        assert_eq!(context.substitute("${unknown} ${version_name}"), "${unknown} 1.20.1");
+        assert_eq!(context.substitute("${resolution_width}"), "${resolution_width}");
+        assert_eq!(context.substitute("${unterminated"), "${unterminated");
+    }
+
+    #[test]
+    fn post113() {
+        let arguments: Arguments = serde_json::from_str(
+            r#"{
+                "game": [
+                    "--username", "${auth_player_name}",
+                    { "rules": [{ "action": "allow", "features": { "is_demo_user": true } }], "value": "--demo" },
+                    { "rules": [{ "action": "allow", "features": { "has_custom_resolution": true } }], "value": ["--width", "${resolution_width}"] }
+                ],
+                "jvm": [
+                    { "rules": [{ "action": "allow", "os": { "name": "osx" } }], "value": ["-XstartOnFirstThread"] },
+                    "-cp", "${classpath}"
+                ]
+            }"#,
+        )
+        .unwrap();
+        let post113 = arguments.to_post113();
+        let context = ArgumentContext { resolution_width: Some(854), ..context() };
+
+        let linux = RuleEnvironment::new("linux", "x86_64");
+        assert_eq!(resolve_arguments(&post113.jvm, &context, &linux), vec!["-cp", "a.jar:b.jar"]);
+        assert_eq!(resolve_arguments(&post113.game, &context, &linux), vec!["--username", "Steve"]);
+
+        let osx = RuleEnvironment::new("osx", "x86_64").with_feature("has_custom_resolution", true);
+        assert_eq!(resolve_arguments(&post113.jvm, &context, &osx), vec!["-XstartOnFirstThread", "-cp", "a.jar:b.jar"]);
+        assert_eq!(resolve_arguments(&post113.game, &context, &osx), vec!["--username", "Steve", "--width", "854"]);
+    }
+
+    #[test]
+    fn pre113() {
+        let arguments = Arguments::Pre113("--username ${auth_player_name}  --version ${version_name}".to_string());
+        let post113 = arguments.to_post113();
+        let context = context();
+        let linux = RuleEnvironment::new("linux", "x86_64");
+
+        assert_eq!(resolve_arguments(&post113.game, &context, &linux), vec!["--username", "Steve", "--version", "1.20.1"]);
+        assert_eq!(
+            resolve_arguments(&post113.game, &context, &linux.clone().with_feature("is_demo_user", true)),
+            vec!["--username", "Steve", "--version", "1.20.1", "--demo"]
+        );
+        assert_eq!(resolve_arguments(&post113.jvm, &context, &linux), vec!["-Djava.library.path=natives", "-cp", "a.jar:b.jar"]);
+        assert_eq!(resolve_arguments(&post113.jvm, &context, &RuleEnvironment::new("windows", "x86_64").with_os_version("10.0")).len(), 6);
+    }
+}
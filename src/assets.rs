@@ -1,12 +1,13 @@
 #![doc = include_str!("../.wiki/Assets.md")]
 
 use crate::assets::AssetError::{AssetFailedToValidate, AssetNotFound};
+use crate::game_directory::GameDirectory;
 use simple_download_utility::{FileDownloadArguments, MultiDownloadProgress, download_multiple_files};
 use crate::sha_validation::validate_file;
 use anyhow::{Result, anyhow};
 use futures_util::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use tokio::io::AsyncWriteExt;
 
@@ -18,6 +19,15 @@ pub struct Assets {
     pub asset_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub path: Option<PathBuf>,
+    /// Set by the `legacy` index, the objects are also needed by name in `assets/virtual/<asset_id>`.
+    #[serde(rename = "virtual", default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_virtual: bool,
+    /// Set by the `pre-1.6` index, the objects are also needed by name in `<game directory>/resources`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub map_to_resources: bool,
+    /// Where the objects are downloaded from, `https://resources.download.minecraft.net` if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources_url: Option<String>,
     pub objects: HashMap<String, AssetItem>,
 }
 
@@ -36,6 +46,9 @@ pub struct AssetValidationResult {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AssetValidationFailureResult {
+    /// The name of the object in the index, e.g. `minecraft/sounds/ambient/cave/cave1.ogg`.
+    #[serde(default)]
+    pub name: String,
     pub hash: String,
     pub reason: AssetValidationFailureReason,
 }
@@ -46,6 +59,51 @@ pub enum AssetValidationFailureReason {
     HashNotMatching,
 }
 
+/// What [`Assets::repair`] started from and how the repaired objects validate afterwards.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AssetRepairReport {
+    pub asset_id: String,
+    /// The failures of the validation the repair was given.
+    pub before: Vec<AssetValidationFailureResult>,
+    /// The validation of the repaired objects after downloading them again.
+    pub after: AssetValidationResult,
+}
+
+/// Whether [`Assets::collect_garbage`] deletes the orphaned objects or only reports them.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GarbageCollectionMode {
+    DryRun,
+    Delete,
+}
+
+/// The objects [`Assets::collect_garbage`] found no index referencing.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AssetGarbageReport {
+    pub orphaned: Vec<PathBuf>,
+    /// The size of the orphaned objects, freed only in [`GarbageCollectionMode::Delete`].
+    pub reclaimed_bytes: u64,
+    pub deleted: bool,
+}
+
+/// How [`Assets::download_incremental`] decides that an object already in the store does not need downloading.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetCheck {
+    /// Only compare the file size, which is cheap but misses corruption that keeps the size.
+    Size,
+    /// Compare the sha1 of the file, see [`AssetItem::validate`].
+    Hash,
+}
+
+/// What [`Assets::download_incremental`] skipped and fetched, objects shared by several names count once.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AssetDownloadReport {
+    pub asset_id: String,
+    pub skipped: usize,
+    pub skipped_bytes: u64,
+    pub fetched: usize,
+    pub fetched_bytes: u64,
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum AssetError {
     #[error("Asset '{name}' not found in path '{path}'")]
@@ -60,13 +118,11 @@ impl Assets {
         debug!("Fetching versions manifest");
 
         let manifest = reqwest::get(url).await?.json::<serde_json::Value>().await?;
-        let objects = manifest.get("objects").ok_or_else(|| anyhow!("missing `objects`"))?;
-        let objects: HashMap<String, AssetItem> = serde_json::from_value(objects.clone())?;
-        debug!("Found {} versions in manifest", objects.len());
-
         let id = url.split("/").last().ok_or_else(|| anyhow!("invalid url"))?.trim_end_matches(".json");
+        let assets = Self::from_index(id, &manifest)?;
+        debug!("Found {} versions in manifest", assets.objects.len());
 
-        Ok(Assets { asset_id: id.to_string(), path: None, url: url.to_string(), objects })
+        Ok(Assets { url: url.to_string(), ..assets })
     }
 
     pub async fn from_path(path: impl AsRef<Path>) -> Result<Self> {
@@ -77,33 +133,288 @@ impl Assets {
         Ok(assets)
     }
 
+    /// Reads the asset index `asset_id` installed in `game_directory`, see [`Assets::install`].
+    pub async fn from_game_directory(game_directory: &GameDirectory, asset_id: impl AsRef<str>) -> Result<Self> {
+        let asset_id = asset_id.as_ref();
+        let content = tokio::fs::read_to_string(game_directory.asset_index(asset_id)).await?;
+        let index = serde_json::from_str::<serde_json::Value>(&content)?;
+        let assets = Self::from_index(asset_id, &index)?;
+
+        Ok(Assets { path: Some(game_directory.asset_objects()), ..assets })
+    }
+
+    /// Reads every asset index installed in `assets/indexes` of `game_directory`.
+    pub async fn installed(game_directory: &GameDirectory) -> Result<Vec<Self>> {
+        let mut entries = match tokio::fs::read_dir(game_directory.asset_indexes()).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+        let mut indexes = vec![];
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|extension| extension == "json")
+                && let Some(asset_id) = path.file_stem()
+            {
+                indexes.push(Self::from_game_directory(game_directory, asset_id.to_string_lossy()).await?);
+            }
+        }
+        Ok(indexes)
+    }
+
+    /// Finds the objects in `objects_directory` that none of `indexes` references and, in
+    /// [`GarbageCollectionMode::Delete`], deletes them. Shard directories that deleting them leaves
+    /// empty are removed as well, directories that were empty before are left alone.
+    ///
+    /// Only files named by a sha1 inside the two hex digit shard directory of that sha1 are
+    /// considered objects; everything else in `objects_directory` is never touched.
+    ///
+    /// Every index that still uses the store has to be passed, see [`Assets::installed`].
+    pub async fn collect_garbage(objects_directory: impl AsRef<Path>, indexes: &[Assets], mode: GarbageCollectionMode) -> Result<AssetGarbageReport> {
+        let objects_directory = objects_directory.as_ref().to_path_buf();
+        let referenced: HashSet<String> = indexes.iter().flat_map(|assets| assets.objects.values()).map(|item| item.hash.to_lowercase()).collect();
+
+        let report = tokio::task::spawn_blocking(move || -> Result<AssetGarbageReport> {
+            let mut report = AssetGarbageReport { deleted: mode == GarbageCollectionMode::Delete, ..Default::default() };
+            let directories = match std::fs::read_dir(&objects_directory) {
+                Ok(directories) => directories,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(report),
+                Err(err) => return Err(err.into()),
+            };
+            for directory in directories {
+                let directory = directory?;
+                let shard = directory.file_name().to_string_lossy().to_lowercase();
+                if !is_hex(&shard, 2) || !directory.file_type()?.is_dir() {
+                    continue;
+                }
+                let directory = directory.path();
+                let mut deleted = false;
+                for file in std::fs::read_dir(&directory)? {
+                    let file = file?;
+                    let hash = file.file_name().to_string_lossy().to_lowercase();
+                    if !is_hex(&hash, 40) || !hash.starts_with(&shard) || referenced.contains(&hash) || !file.file_type()?.is_file() {
+                        continue;
+                    }
+                    report.reclaimed_bytes += file.metadata()?.len();
+                    if mode == GarbageCollectionMode::Delete {
+                        std::fs::remove_file(file.path())?;
+                        deleted = true;
+                    }
+                    report.orphaned.push(file.path());
+                }
+                if deleted {
+                    // Only succeeds for directories that are empty now.
+                    _ = std::fs::remove_dir(&directory);
+                }
+            }
+            Ok(report)
+        })
+        .await??;
+        info!("Found {} orphaned assets ({} bytes)", report.orphaned.len(), report.reclaimed_bytes);
+
+        Ok(report)
+    }
+
+    /// Reads an asset index in the format Mojang publishes, `{"objects": {..}}` and the optional layout flags.
+    fn from_index(asset_id: &str, index: &serde_json::Value) -> Result<Self> {
+        let objects = index.get("objects").ok_or_else(|| anyhow!("missing `objects`"))?;
+        let objects: HashMap<String, AssetItem> = serde_json::from_value(objects.clone())?;
+        let flag = |name: &str| index.get(name).and_then(serde_json::Value::as_bool).unwrap_or(false);
+
+        Ok(Assets {
+            url: String::new(),
+            asset_id: asset_id.to_string(),
+            path: None,
+            is_virtual: flag("virtual"),
+            map_to_resources: flag("map_to_resources"),
+            resources_url: None,
+            objects,
+        })
+    }
+
+    /// The asset index in the format Mojang publishes, as the official launcher stores it in `assets/indexes`.
+    pub(crate) fn index(&self) -> serde_json::Value {
+        let mut index = serde_json::json!({ "objects": self.objects });
+        if self.is_virtual {
+            index["virtual"] = true.into();
+        }
+        if self.map_to_resources {
+            index["map_to_resources"] = true.into();
+        }
+        index
+    }
+
+    /// The directory the objects are needed in by name, `None` for indexes that only use `assets/objects`.
+    pub fn named_directory(&self, game_directory: &GameDirectory) -> Option<PathBuf> {
+        if self.map_to_resources {
+            Some(game_directory.resources())
+        } else if self.is_virtual {
+            Some(game_directory.virtual_assets(&self.asset_id))
+        } else {
+            None
+        }
+    }
+
+    /// The `${game_assets}` directory of versions using this index.
+    pub fn game_assets(&self, game_directory: &GameDirectory) -> PathBuf {
+        self.named_directory(game_directory).unwrap_or_else(|| game_directory.assets())
+    }
+
+    /// Copies the objects installed in `game_directory` to their names in [`Assets::named_directory`],
+    /// which is what versions before 1.7 load their sounds and languages from.
+    ///
+    /// Does nothing for indexes that set neither `virtual` nor `map_to_resources`.
+    pub async fn reconstruct(&self, game_directory: &GameDirectory) -> Result<()> {
+        match self.named_directory(game_directory) {
+            Some(directory) => self.reconstruct_to(game_directory.asset_objects(), directory).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Copies the objects in `objects_directory` to their names in `directory`, for assets
+    /// downloaded with [`Assets::download`] into a layout other than a [`GameDirectory`].
+    ///
+    /// Unlike [`Assets::reconstruct`] this copies the objects whether or not the index needs them by name.
+    pub async fn reconstruct_to(&self, objects_directory: impl AsRef<Path>, directory: impl AsRef<Path>) -> Result<()> {
+        let (objects, directory) = (objects_directory.as_ref().to_path_buf(), directory.as_ref().to_path_buf());
+        debug!("Reconstructing {} assets in {}", self.objects.len(), directory.display());
+        let items: Vec<_> = self.objects.iter().map(|(name, item)| (directory.join(name), item.clone())).collect();
+
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            for (path, item) in items {
+                if validate_file(&path, &item.hash) {
+                    continue;
+                }
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::copy(item.get_download_path(&objects), &path)?;
+            }
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Writes the asset index to `assets/indexes` and downloads the objects to `assets/objects`
+    /// of `game_directory`, the layout the official launcher uses. Legacy indexes are then
+    /// reconstructed, see [`Assets::reconstruct`].
+    pub async fn install(
+        &mut self,
+        game_directory: &GameDirectory,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<()> {
+        self.write_index(game_directory).await?;
+        self.download_objects(&game_directory.asset_objects(), None, parallel, sender).await?;
+        self.reconstruct(game_directory).await
+    }
+
+    /// Writes the asset index to `directory` and downloads the objects next to it.
+    ///
+    /// Legacy indexes are not reconstructed, as only the caller knows where the version looks for
+    /// its assets by name; use [`Assets::install`] or [`Assets::reconstruct_to`] for them.
     pub async fn download(
         &mut self,
         directory: impl AsRef<Path>,
         parallel: u16,
         sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
     ) -> Result<()> {
-        let directory = directory.as_ref();
+        self.download_checked(directory.as_ref(), None, parallel, sender).await?;
+        Ok(())
+    }
+
+    /// Like [`Assets::download`], but objects that already pass `check` are not downloaded again,
+    /// so a store shared between versions only fetches what is missing or corrupt.
+    pub async fn download_incremental(
+        &mut self,
+        directory: impl AsRef<Path>,
+        check: AssetCheck,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<AssetDownloadReport> {
+        self.download_checked(directory.as_ref(), Some(check), parallel, sender).await
+    }
+
+    async fn download_checked(
+        &mut self,
+        directory: &Path,
+        check: Option<AssetCheck>,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<AssetDownloadReport> {
         if !directory.exists() {
             tokio::fs::create_dir_all(&directory).await?;
         }
-        self.path = Some(directory.to_path_buf());
         let mut file = tokio::fs::File::create(directory.join(format!("{}.json", self.asset_id))).await?;
         file.write_all(serde_json::to_string(&self)?.as_bytes()).await?;
-        let download_items: Vec<FileDownloadArguments> = self
-            .objects
-            .values()
-            .map(|item| FileDownloadArguments {
-                url: item.get_download_url(),
-                sha1: Some(item.hash.clone()),
-                sender: None,
-                path: item.get_download_path(directory).to_string_lossy().into_owned(),
-            })
-            .collect();
+        self.download_objects(directory, check, parallel, sender).await
+    }
+
+    /// Writes the asset index to `assets/indexes` of `game_directory` in the format Mojang publishes.
+    pub(crate) async fn write_index(&self, game_directory: &GameDirectory) -> Result<()> {
+        tokio::fs::create_dir_all(game_directory.asset_indexes()).await?;
+        tokio::fs::write(game_directory.asset_index(&self.asset_id), serde_json::to_string(&self.index())?).await?;
+        Ok(())
+    }
+
+    /// Downloads the objects that do not pass `check` into `directory`, every object without a check,
+    /// and makes it the path of these assets.
+    async fn download_objects(
+        &mut self,
+        directory: &Path,
+        check: Option<AssetCheck>,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<AssetDownloadReport> {
+        self.path = Some(directory.to_path_buf());
+        let mut hashes = HashSet::new();
+        let items: Vec<_> = self.objects.values().filter(|item| hashes.insert(item.hash.clone())).cloned().collect();
 
+        let (present, missing): (Vec<_>, Vec<_>) = match check {
+            Some(check) => {
+                let results: Vec<_> = stream::iter(items)
+                    .map(|item| {
+                        let directory = directory.to_path_buf();
+                        tokio::task::spawn_blocking(move || {
+                            let present = item.is_present(&directory, check);
+                            (item, present)
+                        })
+                    })
+                    .buffer_unordered(parallel as usize)
+                    .collect()
+                    .await;
+                results.into_iter().collect::<Result<Vec<_>, _>>()?.into_iter().partition(|(_, present)| *present)
+            }
+            None => (vec![], items.into_iter().map(|item| (item, false)).collect()),
+        };
+        debug!("Skipping {} assets, downloading {}", present.len(), missing.len());
+
+        let download_items = missing.iter().map(|(item, _)| self.download_item(item, directory)).collect();
         download_multiple_files(download_items, parallel, sender).await?;
 
-        Ok(())
+        Ok(AssetDownloadReport {
+            asset_id: self.asset_id.clone(),
+            skipped: present.len(),
+            skipped_bytes: present.iter().map(|(item, _)| item.size).sum(),
+            fetched: missing.len(),
+            fetched_bytes: missing.iter().map(|(item, _)| item.size).sum(),
+        })
+    }
+
+    fn download_item(&self, item: &AssetItem, directory: &Path) -> FileDownloadArguments {
+        FileDownloadArguments {
+            url: item.download_url(self.resources_url.as_deref().unwrap_or(MINECRAFT_RESOURCE_CDN)),
+            sha1: Some(item.hash.clone()),
+            sender: None,
+            path: item.get_download_path(directory).to_string_lossy().into_owned(),
+        }
+    }
+
+    /// The download of every object into `directory`, objects shared by several names only once.
+    #[cfg(feature = "installer")]
+    pub(crate) fn download_items(&self, directory: &Path) -> Vec<FileDownloadArguments> {
+        let mut hashes = HashSet::new();
+        self.objects.values().filter(|item| hashes.insert(item.hash.clone())).map(|item| self.download_item(item, directory)).collect()
     }
 
     pub async fn validate(&self, parallel: u16) -> Result<AssetValidationResult> {
@@ -126,25 +437,73 @@ impl Assets {
             let (name, validation) = join_result?;
             match validation {
                 Ok(_) => result.succeeded.push(name),
-                Err(err) => result.failed.push(AssetValidationFailureResult {
-                    hash: name,
-                    reason: match err {
-                        AssetNotFound { .. } => AssetValidationFailureReason::FileNotFound,
-                        AssetFailedToValidate { .. } => AssetValidationFailureReason::HashNotMatching,
-                    },
-                }),
+                Err(AssetNotFound { name: hash, .. }) => {
+                    result.failed.push(AssetValidationFailureResult { name, hash, reason: AssetValidationFailureReason::FileNotFound })
+                }
+                Err(AssetFailedToValidate { name: hash, .. }) => {
+                    result.failed.push(AssetValidationFailureResult { name, hash, reason: AssetValidationFailureReason::HashNotMatching })
+                }
             }
         }
 
         Ok(result)
     }
+
+    /// Deletes the corrupt objects of `validation`, downloads every failed object again and
+    /// validates them once more. Objects that succeeded are not touched.
+    pub async fn repair(
+        &self,
+        validation: &AssetValidationResult,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<AssetRepairReport> {
+        let path = self.path.as_ref().ok_or_else(|| anyhow!("Asset path was not set"))?;
+
+        let mut failed = HashMap::new();
+        for failure in &validation.failed {
+            let item = self.objects.get(&failure.name).ok_or_else(|| anyhow!("Asset '{}' is not part of index '{}'", failure.name, self.asset_id))?;
+            if let AssetValidationFailureReason::HashNotMatching = failure.reason {
+                match tokio::fs::remove_file(item.get_download_path(path)).await {
+                    Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+                    _ => {}
+                }
+            }
+            failed.insert(failure.name.clone(), item.clone());
+        }
+
+        let mut hashes = HashSet::new();
+        let download_items: Vec<FileDownloadArguments> =
+            failed.values().filter(|item| hashes.insert(item.hash.clone())).map(|item| self.download_item(item, path)).collect();
+        info!("Repairing {} assets of {}", download_items.len(), self.asset_id);
+        download_multiple_files(download_items, parallel, sender).await?;
+
+        let repaired = Assets { objects: failed, ..self.clone() };
+        Ok(AssetRepairReport { asset_id: self.asset_id.clone(), before: validation.failed.clone(), after: repaired.validate(parallel).await? })
+    }
+}
+
+/// Whether `name` consists of exactly `length` lowercase hex digits.
+fn is_hex(name: &str, length: usize) -> bool {
+    name.len() == length && name.bytes().all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+impl AssetRepairReport {
+    /// Whether every failed object validates after the repair.
+    pub fn is_repaired(&self) -> bool {
+        self.after.failed.is_empty()
+    }
 }
 
 impl AssetItem {
     pub fn get_download_url(&self) -> String {
+        self.download_url(MINECRAFT_RESOURCE_CDN)
+    }
+
+    /// The url of the object on the resource server at `resources_url`.
+    pub fn download_url(&self, resources_url: &str) -> String {
         let hash = self.hash.clone();
         let dir = hash.chars().take(2).collect::<String>();
-        format!("{}/{}/{}", MINECRAFT_RESOURCE_CDN, dir, hash)
+        format!("{}/{}/{}", resources_url.trim_end_matches('/'), dir, hash)
     }
     pub fn get_download_path(&self, asset_dir: impl AsRef<Path>) -> PathBuf {
         let asset_dir = asset_dir.as_ref();
@@ -153,6 +512,14 @@ impl AssetItem {
         asset_dir.join(dir).join(&hash)
     }
 
+    /// Whether the object is present in `asset_dir` according to `check`.
+    pub fn is_present(&self, asset_dir: impl AsRef<Path>, check: AssetCheck) -> bool {
+        match check {
+            AssetCheck::Size => std::fs::metadata(self.get_download_path(asset_dir)).is_ok_and(|metadata| metadata.len() == self.size),
+            AssetCheck::Hash => self.validate(asset_dir).is_ok(),
+        }
+    }
+
     pub fn validate(&self, asset_dir: impl AsRef<Path>) -> Result<(), AssetError> {
         let file_path = self.get_download_path(&asset_dir);
         if !file_path.exists() {
@@ -166,3 +533,180 @@ impl AssetItem {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::assets::*;
+    use crate::serve_test_responses;
+
+    #[tokio::test]
+    async fn legacy_layouts() {
+        let directory = GameDirectory::new("target/test/assets-legacy");
+        _ = std::fs::remove_dir_all(directory.root());
+        let sound = AssetItem { hash: "a9993e364706816aba3e25717850c26c9cd0d89d".to_string(), size: 3 };
+        std::fs::create_dir_all(sound.get_download_path(directory.asset_objects()).parent().unwrap()).unwrap();
+        std::fs::write(sound.get_download_path(directory.asset_objects()), b"abc").unwrap();
+        std::fs::create_dir_all(directory.asset_indexes()).unwrap();
+
+        for (id, flag, named) in [
+            ("legacy", "virtual", directory.root().join("assets/virtual/legacy")),
+            ("pre-1.6", "map_to_resources", directory.root().join("resources")),
+        ] {
+            let index = format!(r#"{{ "{}": true, "objects": {{ "sound/step/grass1.ogg": {{ "hash": "{}", "size": 3 }} }} }}"#, flag, sound.hash);
+            std::fs::write(directory.asset_index(id), index).unwrap();
+            let assets = Assets::from_game_directory(&directory, id).await.unwrap();
+            assert_eq!(assets.named_directory(&directory), Some(named.clone()));
+            assert_eq!(assets.game_assets(&directory), named);
+            assert_eq!(assets.index()[flag], true);
+
+            assets.reconstruct(&directory).await.unwrap();
+            assert_eq!(std::fs::read(named.join("sound/step/grass1.ogg")).unwrap(), b"abc");
+        }
+
+        let custom = directory.root().join("custom");
+        let assets = Assets::from_game_directory(&directory, "legacy").await.unwrap();
+        assets.reconstruct_to(directory.asset_objects(), &custom).await.unwrap();
+        assert_eq!(std::fs::read(custom.join("sound/step/grass1.ogg")).unwrap(), b"abc");
+
+        std::fs::write(directory.asset_index("5"), r#"{ "objects": {} }"#).unwrap();
+        let assets = Assets::from_game_directory(&directory, "5").await.unwrap();
+        assert!(!assets.is_virtual && !assets.map_to_resources);
+        assert_eq!(assets.game_assets(&directory), directory.assets());
+        assert_eq!(assets.index(), serde_json::json!({ "objects": {} }));
+    }
+
+    #[test]
+    fn present_objects() {
+        let directory = Path::new("target/test/assets-present");
+        _ = std::fs::remove_dir_all(directory);
+        let item = AssetItem { hash: "a9993e364706816aba3e25717850c26c9cd0d89d".to_string(), size: 3 };
+        assert!(!item.is_present(directory, AssetCheck::Size));
+
+        std::fs::create_dir_all(directory.join("a9")).unwrap();
+        std::fs::write(item.get_download_path(directory), b"abd").unwrap();
+        assert!(item.is_present(directory, AssetCheck::Size));
+        assert!(!item.is_present(directory, AssetCheck::Hash));
+
+        std::fs::write(item.get_download_path(directory), b"abc").unwrap();
+        assert!(item.is_present(directory, AssetCheck::Hash));
+    }
+
+    #[tokio::test]
+    async fn download_incremental() {
+        let directory = Path::new("target/test/assets-incremental");
+        _ = std::fs::remove_dir_all(directory);
+        let item = AssetItem { hash: "a9993e364706816aba3e25717850c26c9cd0d89d".to_string(), size: 3 };
+        std::fs::create_dir_all(directory.join("a9")).unwrap();
+        std::fs::write(item.get_download_path(directory), b"abc").unwrap();
+        let objects = HashMap::from([("sound/a.ogg".to_string(), item.clone()), ("sound/b.ogg".to_string(), item)]);
+        let mut assets = Assets {
+            url: String::new(),
+            asset_id: "5".to_string(),
+            path: None,
+            is_virtual: false,
+            map_to_resources: false,
+            resources_url: None,
+            objects,
+        };
+
+        let report = assets.download_incremental(directory, AssetCheck::Hash, 4, None).await.unwrap();
+        assert_eq!(report, AssetDownloadReport { asset_id: "5".to_string(), skipped: 1, skipped_bytes: 3, fetched: 0, fetched_bytes: 0 });
+        assert!(directory.join("5.json").exists());
+    }
+
+    #[tokio::test]
+    async fn collect_garbage() {
+        let directory = GameDirectory::new("target/test/assets-garbage");
+        _ = std::fs::remove_dir_all(directory.root());
+        let objects = directory.asset_objects();
+        for (hash, content) in [
+            ("a9993e364706816aba3e25717850c26c9cd0d89d", "abc"),
+            ("0123456789abcdef0123456789abcdef01234567", "orphan"),
+            ("0288888888888888888888888888888888888888", "orphan too"),
+        ] {
+            std::fs::create_dir_all(objects.join(&hash[..2])).unwrap();
+            std::fs::write(objects.join(&hash[..2]).join(hash), content).unwrap();
+        }
+        // Files that are not objects are left alone: next to the shards, in other directories and
+        // sha1 named files in the wrong shard.
+        std::fs::write(objects.join("notes.txt"), "not an object").unwrap();
+        std::fs::create_dir_all(objects.join("virtual/legacy")).unwrap();
+        std::fs::write(objects.join("virtual/legacy/sound.ogg"), "not an object").unwrap();
+        std::fs::write(objects.join("01/a9993e364706816aba3e25717850c26c9cd0d89e"), "misplaced").unwrap();
+        std::fs::write(objects.join("01/readme"), "not an object").unwrap();
+        std::fs::create_dir_all(directory.asset_indexes()).unwrap();
+        std::fs::write(
+            directory.asset_index("5"),
+            r#"{ "objects": { "sound/a.ogg": { "hash": "a9993e364706816aba3e25717850c26c9cd0d89d", "size": 3 } } }"#,
+        )
+        .unwrap();
+        std::fs::write(directory.asset_indexes().join("notes.txt"), "not an index").unwrap();
+        std::fs::create_dir_all(objects.join("ff")).unwrap();
+
+        let indexes = Assets::installed(&directory).await.unwrap();
+        assert_eq!(indexes.len(), 1);
+        let report = Assets::collect_garbage(&objects, &indexes, GarbageCollectionMode::DryRun).await.unwrap();
+        assert_eq!(report.orphaned.len(), 2);
+        assert_eq!(report.reclaimed_bytes, 16);
+        assert!(!report.deleted);
+        assert!(objects.join("01").exists());
+
+        let report = Assets::collect_garbage(&objects, &indexes, GarbageCollectionMode::Delete).await.unwrap();
+        assert_eq!(report.reclaimed_bytes, 16);
+        assert!(report.deleted);
+        assert!(!objects.join("01/0123456789abcdef0123456789abcdef01234567").exists());
+        assert!(!objects.join("02").exists());
+        assert!(objects.join("01/a9993e364706816aba3e25717850c26c9cd0d89e").exists());
+        assert!(objects.join("01/readme").exists());
+        assert!(objects.join("notes.txt").exists());
+        assert!(objects.join("virtual/legacy/sound.ogg").exists());
+        assert!(objects.join("ff").exists());
+        assert!(objects.join("a9/a9993e364706816aba3e25717850c26c9cd0d89d").exists());
+
+        // The assets directory itself holds no shards, its indexes survive.
+        let report = Assets::collect_garbage(directory.assets(), &[], GarbageCollectionMode::Delete).await.unwrap();
+        assert!(report.orphaned.is_empty());
+        assert!(directory.asset_index("5").exists());
+
+        let report = Assets::collect_garbage(&objects, &[], GarbageCollectionMode::DryRun).await.unwrap();
+        assert_eq!(report.orphaned, [objects.join("a9/a9993e364706816aba3e25717850c26c9cd0d89d")]);
+    }
+
+    #[tokio::test]
+    async fn repair() {
+        let hash = "a9993e364706816aba3e25717850c26c9cd0d89d";
+        let base_url = serve_test_responses(vec![("/a9/a9993e364706816aba3e25717850c26c9cd0d89d", "abc")]).await;
+        let directory = Path::new("target/test/assets-repair");
+        _ = std::fs::remove_dir_all(directory);
+        std::fs::create_dir_all(directory.join("a9")).unwrap();
+        std::fs::write(directory.join("a9").join(hash), b"abd").unwrap();
+
+        let item = AssetItem { hash: hash.to_string(), size: 3 };
+        let assets = Assets {
+            url: String::new(),
+            asset_id: "5".to_string(),
+            path: Some(directory.to_path_buf()),
+            is_virtual: false,
+            map_to_resources: false,
+            resources_url: Some(base_url),
+            objects: HashMap::from([("sound/a.ogg".to_string(), item)]),
+        };
+        let validation = assets.validate(4).await.unwrap();
+        assert_eq!(validation.failed[0].name, "sound/a.ogg");
+        assert_eq!(validation.failed[0].hash, hash);
+
+        let report = assets.repair(&validation, 4, None).await.unwrap();
+        assert!(report.is_repaired());
+        assert!(matches!(report.before[0].reason, AssetValidationFailureReason::HashNotMatching));
+        assert_eq!(report.after.succeeded, ["sound/a.ogg"]);
+        assert_eq!(std::fs::read(directory.join("a9").join(hash)).unwrap(), b"abc");
+
+        let unknown = AssetValidationFailureResult {
+            name: "sound/unknown.ogg".to_string(),
+            hash: hash.to_string(),
+            reason: AssetValidationFailureReason::FileNotFound,
+        };
+        let validation = AssetValidationResult { failed: vec![unknown], ..validation };
+        assert!(assets.repair(&validation, 4, None).await.is_err());
+    }
+}
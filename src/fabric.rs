@@ -0,0 +1,158 @@
+//! Client for the Fabric meta API.
+//!
+//! Lists the game, loader and intermediary versions Fabric supports, and fetches the launcher
+//! profile for a game and loader version pair. The profile is a child [`VersionManifest`] that
+//! inherits from the vanilla version, use [`VersionManifest::resolve`] to get a launchable manifest.
+//!
+//! # Example
+//! ```no_run
+//! use piston_mc::fabric::FabricMeta;
+//! use piston_mc::manifest_v2::ManifestV2;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let fabric = FabricMeta::default();
+//!     let loader = fabric.loader_versions().await.unwrap().into_iter().find(|loader| loader.stable).unwrap();
+//!     let profile = fabric.profile("1.20.1", &loader.version).await.unwrap();
+//!     let version = profile.resolve(&ManifestV2::fetch().await.unwrap()).await.unwrap();
+//!     println!("{} launches {}", version.id, version.main_class);
+//! }
+//! ```
+
+use crate::http::fetch_json;
+use crate::version_manifest::VersionManifest;
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const FABRIC_META_URL: &str = "https://meta.fabricmc.net/v2";
+
+/// Entry point to the Fabric meta API, [`FabricMeta::default`] talks to the official instance.
+#[derive(Clone, Debug)]
+pub struct FabricMeta {
+    base_url: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FabricGameVersion {
+    pub version: String,
+    pub stable: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FabricLoaderVersion {
+    pub separator: String,
+    pub build: u32,
+    /// Maven coordinate of the loader, e.g. `net.fabricmc:fabric-loader:0.15.11`.
+    pub maven: String,
+    pub version: String,
+    pub stable: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FabricIntermediaryVersion {
+    /// Maven coordinate of the intermediary mappings, e.g. `net.fabricmc:intermediary:1.20.1`.
+    pub maven: String,
+    pub version: String,
+    pub stable: bool,
+}
+
+impl Default for FabricMeta {
+    fn default() -> Self {
+        Self::with_base_url(FABRIC_META_URL)
+    }
+}
+
+impl FabricMeta {
+    /// Talks to a Fabric meta instance other than the official one, e.g. a mirror.
+    pub fn with_base_url(base_url: impl AsRef<str>) -> Self {
+        Self { base_url: base_url.as_ref().trim_end_matches('/').to_string() }
+    }
+
+    pub async fn game_versions(&self) -> Result<Vec<FabricGameVersion>> {
+        self.fetch("versions/game").await
+    }
+
+    pub async fn loader_versions(&self) -> Result<Vec<FabricLoaderVersion>> {
+        self.fetch("versions/loader").await
+    }
+
+    pub async fn intermediary_versions(&self) -> Result<Vec<FabricIntermediaryVersion>> {
+        self.fetch("versions/intermediary").await
+    }
+
+    /// Fetches the launcher profile of `loader_version` for `game_version`.
+    pub async fn profile(&self, game_version: impl AsRef<str>, loader_version: impl AsRef<str>) -> Result<VersionManifest> {
+        let url = format!("{}/versions/loader/{}/{}/profile/json", self.base_url, game_version.as_ref(), loader_version.as_ref());
+        VersionManifest::from_url(url).await
+    }
+
+    async fn fetch<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>> {
+        let url = format!("{}/{}", self.base_url, path);
+        debug!("Fetching {}", url);
+        let versions: Vec<T> = fetch_json(&url).await?;
+        debug!("Found {} entries at {}", versions.len(), url);
+        Ok(versions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fabric::FabricMeta;
+    use crate::serve_test_responses;
+    #[cfg(feature = "log")]
+    use crate::setup_logging;
+
+    #[tokio::test]
+    async fn versions() {
+        #[cfg(feature = "log")]
+        setup_logging();
+        let base_url = serve_test_responses(vec![
+            ("/v2/versions/game", r#"[{ "version": "1.20.1", "stable": true }, { "version": "23w31a", "stable": false }]"#.to_string()),
+            (
+                "/v2/versions/loader",
+                r#"[{ "separator": ".", "build": 11, "maven": "net.fabricmc:fabric-loader:0.15.11", "version": "0.15.11", "stable": true }]"#
+                    .to_string(),
+            ),
+            ("/v2/versions/intermediary", r#"[{ "maven": "net.fabricmc:intermediary:1.20.1", "version": "1.20.1", "stable": true }]"#.to_string()),
+        ])
+        .await;
+        let fabric = FabricMeta::with_base_url(format!("{}/v2/", base_url));
+
+        let games = fabric.game_versions().await.unwrap();
+        assert_eq!(games.len(), 2);
+        assert!(!games[1].stable);
+
+        let loaders = fabric.loader_versions().await.unwrap();
+        assert_eq!(loaders[0].maven, "net.fabricmc:fabric-loader:0.15.11");
+
+        let intermediaries = fabric.intermediary_versions().await.unwrap();
+        assert_eq!(intermediaries[0].version, "1.20.1");
+    }
+
+    #[tokio::test]
+    async fn profile() {
+        #[cfg(feature = "log")]
+        setup_logging();
+        let profile = r#"{
+            "id": "fabric-loader-0.15.11-1.20.1",
+            "inheritsFrom": "1.20.1",
+            "releaseTime": "2024-05-06T19:41:27+0000",
+            "time": "2024-05-06T19:41:27+0000",
+            "type": "release",
+            "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+            "arguments": { "game": [], "jvm": ["-DFabricMcEmu= net.minecraft.client.main.Main "] },
+            "libraries": [
+                { "name": "net.fabricmc:intermediary:1.20.1", "url": "https://maven.fabricmc.net/" },
+                { "name": "net.fabricmc:fabric-loader:0.15.11", "url": "https://maven.fabricmc.net/" }
+            ]
+        }"#;
+        let base_url = serve_test_responses(vec![("/v2/versions/loader/1.20.1/0.15.11/profile/json", profile.to_string())]).await;
+        let fabric = FabricMeta::with_base_url(format!("{}/v2", base_url));
+
+        let manifest = fabric.profile("1.20.1", "0.15.11").await.unwrap();
+        assert_eq!(manifest.inherits_from.as_deref(), Some("1.20.1"));
+        assert_eq!(manifest.libraries.len(), 2);
+        assert!(fabric.profile("1.20.1", "0.0.0").await.is_err());
+    }
+}
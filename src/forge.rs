@@ -0,0 +1,493 @@
+//! Installer support for Forge and NeoForge.
+//!
+//! Modern Forge and NeoForge installer jars contain an `install_profile.json` describing the
+//! libraries the installation needs, a set of `data` variables and a list of `processors`:
+//! java programs that patch the vanilla jar into the modded one. [`ForgeInstaller::install`]
+//! extracts the bundled maven repository, downloads the remaining libraries, runs the
+//! processors with the given java executable and verifies their outputs. The returned child
+//! [`VersionManifest`] inherits from the vanilla version; use [`VersionManifest::resolve`]
+//! to get a launchable manifest.
+//!
+//! # Example
+//! ```no_run
+//! use piston_mc::forge::{ForgeInstaller, InstallSide};
+//! use piston_mc::manifest_v2::ManifestV2;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let installer = ForgeInstaller::from_path("forge-1.20.1-47.1.0-installer.jar").unwrap();
+//!     let profile = installer
+//!         .install(".minecraft/libraries", ".minecraft/versions/1.20.1/1.20.1.jar", "java", InstallSide::Client, 20, None)
+//!         .await
+//!         .unwrap();
+//!     let version = profile.resolve(&ManifestV2::fetch().await.unwrap()).await.unwrap();
+//!     println!("{} launches {}", version.id, version.main_class);
+//! }
+//! ```
+
+use crate::arguments::CLASSPATH_SEPARATOR;
+use crate::maven::MavenCoordinate;
+use crate::rules::RuleEnvironment;
+use crate::sha_validation::{SHAError, validate_file};
+use crate::version_manifest::{LibraryItem, VersionManifest};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use simple_download_utility::{FileDownloadArguments, MultiDownloadProgress, download_multiple_files};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// The `install_profile.json` of a Forge or NeoForge installer.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InstallProfile {
+    #[serde(default)]
+    pub spec: u32,
+    pub profile: String,
+    /// Id of the version the installer creates, e.g. `1.20.1-forge-47.1.0`.
+    pub version: String,
+    /// The vanilla version this installer patches.
+    pub minecraft: String,
+    /// Path of the child version manifest inside the installer jar.
+    pub json: String,
+    #[serde(default)]
+    pub data: HashMap<String, SidedData>,
+    #[serde(default)]
+    pub processors: Vec<Processor>,
+    #[serde(default)]
+    pub libraries: Vec<LibraryItem>,
+}
+
+/// A `data` variable, which can differ between the client and server installation.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SidedData {
+    pub client: String,
+    pub server: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Processor {
+    /// Sides this processor runs for, all sides if absent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sides: Option<Vec<String>>,
+    /// Maven coordinate of the jar to run.
+    pub jar: String,
+    #[serde(default)]
+    pub classpath: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Files this processor produces mapped to their expected sha1, both may reference `data` variables.
+    #[serde(default)]
+    pub outputs: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstallSide {
+    #[serde(rename = "client")]
+    Client,
+    #[serde(rename = "server")]
+    Server,
+}
+
+/// An opened Forge or NeoForge installer jar.
+#[derive(Clone, Debug)]
+pub struct ForgeInstaller {
+    pub path: PathBuf,
+    pub profile: InstallProfile,
+    /// The child version manifest the installation produces.
+    pub version: VersionManifest,
+}
+
+impl InstallSide {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InstallSide::Client => "client",
+            InstallSide::Server => "server",
+        }
+    }
+}
+
+impl ForgeInstaller {
+    /// Reads the install profile and version manifest from the installer jar at `path`.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+        let profile: InstallProfile = serde_json::from_str(&read_entry(&mut archive, "install_profile.json")?)
+            .map_err(|e| anyhow!("Unsupported installer {}: {}", path.display(), e))?;
+        let version: VersionManifest = serde_json::from_str(&read_entry(&mut archive, &profile.json)?)?;
+        debug!("Opened {} installer for {}", profile.profile, profile.version);
+        Ok(Self { path: path.to_path_buf(), profile, version })
+    }
+
+    /// Installs the libraries of this installer into `libraries_directory` and runs its processors.
+    ///
+    /// `minecraft_jar` is the already downloaded vanilla client or server jar, depending on `side`,
+    /// and `java` the java executable the processors run with. Processors whose outputs are already
+    /// present with the expected sha1 are skipped, so running the installation again is cheap.
+    pub async fn install(
+        &self,
+        libraries_directory: impl AsRef<Path>,
+        minecraft_jar: impl AsRef<Path>,
+        java: impl AsRef<Path>,
+        side: InstallSide,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<VersionManifest> {
+        let libraries_directory = libraries_directory.as_ref().to_path_buf();
+        // Removed when dropped, whether or not the installation succeeds.
+        let data_directory = self.data_directory()?;
+        let data_directory = data_directory.path();
+
+        let installer = self.clone();
+        let (libraries, data) = (libraries_directory.clone(), data_directory.to_path_buf());
+        tokio::task::spawn_blocking(move || installer.extract_bundled(&libraries, &data)).await??;
+
+        self.download_libraries(&libraries_directory, parallel, sender).await?;
+
+        let variables = self.variables(&libraries_directory, minecraft_jar.as_ref(), data_directory, side)?;
+        for processor in &self.profile.processors {
+            if processor.sides.as_ref().is_some_and(|sides| !sides.iter().any(|s| s == side.as_str())) {
+                continue;
+            }
+            let processor = processor.clone();
+            let (libraries, variables, java) = (libraries_directory.clone(), variables.clone(), java.as_ref().to_path_buf());
+            tokio::task::spawn_blocking(move || run_processor(&processor, &libraries, &variables, &java)).await??;
+        }
+
+        Ok(self.version.clone())
+    }
+
+    /// Creates a new, uniquely named directory for the `data/` files of this installer, so concurrent
+    /// installations of the same version do not share it. The directory is removed when dropped.
+    fn data_directory(&self) -> Result<tempfile::TempDir> {
+        Ok(tempfile::Builder::new().prefix(&format!("piston-mc-{}-", self.profile.version)).tempdir()?)
+    }
+
+    /// Extracts the bundled `maven/` repository into the libraries directory and the `data/` files into `data_directory`.
+    fn extract_bundled(&self, libraries_directory: &Path, data_directory: &Path) -> Result<()> {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&self.path)?)?;
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index)?;
+            let Some(name) = entry.enclosed_name() else {
+                continue;
+            };
+            let path = if let Ok(relative) = name.strip_prefix("maven") {
+                libraries_directory.join(relative)
+            } else if name.starts_with("data") {
+                data_directory.join(name)
+            } else {
+                continue;
+            };
+            if entry.is_dir() {
+                continue;
+            }
+
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::io::copy(&mut entry, &mut std::fs::File::create(&path)?)?;
+        }
+        Ok(())
+    }
+
+    /// Downloads the libraries of the install profile and the version manifest that are not bundled in the installer.
+    async fn download_libraries(
+        &self,
+        libraries_directory: &Path,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<()> {
+        let environment = RuleEnvironment::current();
+        let mut paths = HashSet::new();
+        let download_items: Vec<FileDownloadArguments> = self
+            .profile
+            .libraries
+            .iter()
+            .chain(self.version.libraries.iter())
+            .filter(|library| library.is_allowed(&environment))
+            .filter_map(|library| library.artifact())
+            // Artifacts without a url are either bundled in the installer or produced by a processor.
+            .filter(|artifact| !artifact.url.is_empty())
+            .filter(|artifact| {
+                let path = libraries_directory.join(&artifact.path);
+                let valid = artifact.sha1.as_ref().is_some_and(|sha1| validate_file(&path, sha1));
+                !valid && paths.insert(path)
+            })
+            .map(|artifact| FileDownloadArguments {
+                url: artifact.url,
+                sha1: artifact.sha1,
+                sender: None,
+                path: libraries_directory.join(&artifact.path).to_string_lossy().into_owned(),
+            })
+            .collect();
+
+        debug!("Downloading {} libraries for {}", download_items.len(), self.profile.version);
+        download_multiple_files(download_items, parallel, sender).await?;
+        Ok(())
+    }
+
+    /// Resolves the `data` variables for `side`, including the ones every installer provides.
+    fn variables(
+        &self,
+        libraries_directory: &Path,
+        minecraft_jar: &Path,
+        data_directory: &Path,
+        side: InstallSide,
+    ) -> Result<HashMap<String, String>> {
+        let mut variables = HashMap::from([
+            ("SIDE".to_string(), side.as_str().to_string()),
+            ("MINECRAFT_JAR".to_string(), minecraft_jar.to_string_lossy().into_owned()),
+            ("MINECRAFT_VERSION".to_string(), self.profile.minecraft.clone()),
+            ("INSTALLER".to_string(), self.path.to_string_lossy().into_owned()),
+            ("LIBRARY_DIR".to_string(), libraries_directory.to_string_lossy().into_owned()),
+            ("ROOT".to_string(), libraries_directory.parent().unwrap_or(libraries_directory).to_string_lossy().into_owned()),
+        ]);
+
+        for (key, value) in &self.profile.data {
+            let value = match side {
+                InstallSide::Client => &value.client,
+                InstallSide::Server => &value.server,
+            };
+            let value = if let Some(literal) = value.strip_prefix('\'').and_then(|value| value.strip_suffix('\'')) {
+                literal.to_string()
+            } else if let Some(path) = value.strip_prefix('/') {
+                data_directory.join(path).to_string_lossy().into_owned()
+            } else {
+                resolve_library(value, libraries_directory)?.unwrap_or_else(|| value.clone())
+            };
+            variables.insert(key.clone(), value);
+        }
+
+        Ok(variables)
+    }
+}
+
+/// Resolves `[group:artifact:version]` to the path of that library, or `None` for any other value.
+fn resolve_library(value: &str, libraries_directory: &Path) -> Result<Option<String>> {
+    let Some(coordinate) = value.strip_prefix('[').and_then(|value| value.strip_suffix(']')) else {
+        return Ok(None);
+    };
+    let coordinate: MavenCoordinate = coordinate.parse()?;
+    Ok(Some(libraries_directory.join(coordinate.path()).to_string_lossy().into_owned()))
+}
+
+/// Replaces the `{VARIABLE}` tokens and `[coordinate]` references of a processor argument or output.
+fn substitute(value: &str, variables: &HashMap<String, String>, libraries_directory: &Path) -> Result<String> {
+    if let Some(path) = resolve_library(value, libraries_directory)? {
+        return Ok(path);
+    }
+    if let Some(literal) = value.strip_prefix('\'').and_then(|value| value.strip_suffix('\'')) {
+        return Ok(literal.to_string());
+    }
+
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        let key = &rest[start + 1..start + length];
+        let replacement = variables.get(key).ok_or_else(|| anyhow!("Unknown installer variable '{}' in '{}'", key, value))?;
+        result.push_str(&rest[..start]);
+        result.push_str(replacement);
+        rest = &rest[start + length + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+fn run_processor(processor: &Processor, libraries_directory: &Path, variables: &HashMap<String, String>, java: &Path) -> Result<()> {
+    let outputs: Vec<(String, String)> = processor
+        .outputs
+        .iter()
+        .map(|(path, sha1)| Ok((substitute(path, variables, libraries_directory)?, substitute(sha1, variables, libraries_directory)?)))
+        .collect::<Result<_>>()?;
+    if !outputs.is_empty() && outputs.iter().all(|(path, sha1)| validate_file(path, sha1)) {
+        debug!("Skipping processor {}, its outputs are up to date", processor.jar);
+        return Ok(());
+    }
+
+    let jar = libraries_directory.join(processor.jar.parse::<MavenCoordinate>()?.path());
+    let main_class = main_class(&jar)?;
+    let classpath = std::iter::once(Ok(jar.to_string_lossy().into_owned()))
+        .chain(processor.classpath.iter().map(|coordinate| {
+            let coordinate: MavenCoordinate = coordinate.parse()?;
+            Ok(libraries_directory.join(coordinate.path()).to_string_lossy().into_owned())
+        }))
+        .collect::<Result<Vec<_>>>()?
+        .join(CLASSPATH_SEPARATOR);
+    let arguments = processor.args.iter().map(|argument| substitute(argument, variables, libraries_directory)).collect::<Result<Vec<_>>>()?;
+
+    info!("Running processor {}", processor.jar);
+    let status = std::process::Command::new(java).arg("-cp").arg(classpath).arg(&main_class).args(&arguments).status()?;
+    if !status.success() {
+        return Err(anyhow!("Processor {} failed with {}", processor.jar, status));
+    }
+
+    for (path, sha1) in outputs {
+        if !validate_file(&path, &sha1) {
+            return Err(SHAError::FailedValidation(path).into());
+        }
+    }
+    Ok(())
+}
+
+/// Reads the `Main-Class` attribute from the manifest of `jar`.
+fn main_class(jar: &Path) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(jar)?)?;
+    let manifest = read_entry(&mut archive, "META-INF/MANIFEST.MF")?;
+    manifest
+        .lines()
+        .find_map(|line| line.strip_prefix("Main-Class:"))
+        .map(|main_class| main_class.trim().to_string())
+        .ok_or_else(|| anyhow!("{} has no Main-Class", jar.display()))
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> Result<String> {
+    let mut entry = archive.by_name(name.trim_start_matches('/'))?;
+    let mut content = String::new();
+    entry.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::forge::*;
+    use std::io::Write;
+
+    const INSTALL_PROFILE: &str = r#"{
+        "spec": 1,
+        "profile": "forge",
+        "version": "1.20.1-forge-47.1.0",
+        "path": null,
+        "minecraft": "1.20.1",
+        "json": "/version.json",
+        "data": {
+            "MAPPINGS": { "client": "[de.oceanlabs.mcp:mcp_config:1.20.1-20230612.114412:mappings@txt]", "server": "[de.oceanlabs.mcp:mcp_config:1.20.1-20230612.114412:server-mappings@txt]" },
+            "BINPATCH": { "client": "/data/client.lzma", "server": "/data/server.lzma" },
+            "PATCHED_SHA": { "client": "'abc'", "server": "'def'" }
+        },
+        "processors": [
+            {
+                "sides": ["client"],
+                "jar": "net.minecraftforge:binarypatcher:1.1.1",
+                "classpath": ["commons-io:commons-io:2.4"],
+                "args": ["--clean", "{MINECRAFT_JAR}", "--patch", "{BINPATCH}", "--output", "[net.minecraftforge:forge:1.20.1-47.1.0:client]"],
+                "outputs": { "[net.minecraftforge:forge:1.20.1-47.1.0:client]": "{PATCHED_SHA}" }
+            }
+        ],
+        "libraries": [
+            {
+                "name": "net.minecraftforge:forge:1.20.1-47.1.0:universal",
+                "downloads": { "artifact": { "path": "net/minecraftforge/forge/1.20.1-47.1.0/forge-1.20.1-47.1.0-universal.jar", "sha1": "", "size": 0, "url": "" } }
+            }
+        ]
+    }"#;
+
+    const VERSION: &str = r#"{
+        "id": "1.20.1-forge-47.1.0",
+        "inheritsFrom": "1.20.1",
+        "type": "release",
+        "mainClass": "cpw.mods.bootstraplauncher.BootstrapLauncher",
+        "releaseTime": "2023-06-12T13:25:51+00:00",
+        "time": "2023-06-12T13:25:51+00:00",
+        "arguments": { "game": ["--launchTarget", "forgeclient"], "jvm": [] },
+        "libraries": []
+    }"#;
+
+    fn write_installer(path: &Path) {
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        writer.start_file("install_profile.json", options).unwrap();
+        writer.write_all(INSTALL_PROFILE.as_bytes()).unwrap();
+        writer.start_file("version.json", options).unwrap();
+        writer.write_all(VERSION.as_bytes()).unwrap();
+        writer.start_file("maven/net/minecraftforge/forge/1.20.1-47.1.0/forge-1.20.1-47.1.0-universal.jar", options).unwrap();
+        writer.write_all(b"universal").unwrap();
+        writer.start_file("data/client.lzma", options).unwrap();
+        writer.write_all(b"patch").unwrap();
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn open_and_extract() {
+        let directory = Path::new("target/test/forge");
+        _ = std::fs::remove_dir_all(directory);
+        std::fs::create_dir_all(directory).unwrap();
+        let installer_path = directory.join("installer.jar");
+        write_installer(&installer_path);
+
+        let installer = ForgeInstaller::from_path(&installer_path).unwrap();
+        assert_eq!(installer.profile.version, "1.20.1-forge-47.1.0");
+        assert_eq!(installer.profile.processors[0].sides.as_deref(), Some(&["client".to_string()][..]));
+        assert_eq!(installer.version.inherits_from.as_deref(), Some("1.20.1"));
+
+        let libraries = directory.join("libraries");
+        let data = directory.join("data");
+        installer.extract_bundled(&libraries, &data).unwrap();
+        let universal = libraries.join("net/minecraftforge/forge/1.20.1-47.1.0/forge-1.20.1-47.1.0-universal.jar");
+        assert_eq!(std::fs::read(universal).unwrap(), b"universal");
+        assert_eq!(std::fs::read(data.join("data/client.lzma")).unwrap(), b"patch");
+    }
+
+    #[test]
+    fn unique_data_directories() {
+        let directory = Path::new("target/test/forge-data");
+        _ = std::fs::remove_dir_all(directory);
+        std::fs::create_dir_all(directory).unwrap();
+        let installer_path = directory.join("installer.jar");
+        write_installer(&installer_path);
+
+        let installer = ForgeInstaller::from_path(&installer_path).unwrap();
+        let first = installer.data_directory().unwrap();
+        let second = installer.data_directory().unwrap();
+        assert_ne!(first.path(), second.path());
+        assert!(first.path().is_dir() && second.path().is_dir());
+
+        let path = first.path().to_path_buf();
+        drop(first);
+        assert!(!path.exists());
+        assert!(second.path().is_dir());
+    }
+
+    #[tokio::test]
+    async fn failed_install() {
+        let directory = Path::new("target/test/forge-failed");
+        _ = std::fs::remove_dir_all(directory);
+        std::fs::create_dir_all(directory).unwrap();
+        let installer_path = directory.join("installer.jar");
+        write_installer(&installer_path);
+
+        let installer = ForgeInstaller::from_path(&installer_path).unwrap();
+        let result = installer
+            .install(directory.join("libraries"), directory.join("client.jar"), directory.join("missing-java"), InstallSide::Client, 1, None)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn variables_and_arguments() {
+        let profile: InstallProfile = serde_json::from_str(INSTALL_PROFILE).unwrap();
+        let version: VersionManifest = serde_json::from_str(VERSION).unwrap();
+        let installer = ForgeInstaller { path: PathBuf::from("installer.jar"), profile, version };
+        let libraries = Path::new("libraries");
+        let data = Path::new("data");
+
+        let variables = installer.variables(libraries, Path::new("client.jar"), data, InstallSide::Client).unwrap();
+        assert_eq!(variables["SIDE"], "client");
+        assert_eq!(variables["MINECRAFT_VERSION"], "1.20.1");
+        assert_eq!(variables["PATCHED_SHA"], "abc");
+        assert_eq!(variables["BINPATCH"], data.join("data/client.lzma").to_string_lossy());
+        assert_eq!(
+            variables["MAPPINGS"],
+            libraries.join("de/oceanlabs/mcp/mcp_config/1.20.1-20230612.114412/mcp_config-1.20.1-20230612.114412-mappings.txt").to_string_lossy()
+        );
+
+        assert_eq!(substitute("{MINECRAFT_JAR}", &variables, libraries).unwrap(), "client.jar");
+        assert_eq!(substitute("--side={SIDE}", &variables, libraries).unwrap(), "--side=client");
+        assert_eq!(
+            substitute("[net.minecraftforge:forge:1.20.1-47.1.0:client]", &variables, libraries).unwrap(),
+            libraries.join("net/minecraftforge/forge/1.20.1-47.1.0/forge-1.20.1-47.1.0-client.jar").to_string_lossy()
+        );
+        assert!(substitute("{MISSING}", &variables, libraries).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+//! The directory layout of the official launcher, usually called `.minecraft`.
+//!
+//! Installing into a [`GameDirectory`] puts every file where the official launcher and most
+//! third party launchers expect it, so installations can be shared between them.
+//!
+//! ```text
+//! versions/<id>/<id>.json
+//! versions/<id>/<id>.jar
+//! versions/<id>/natives/
+//! libraries/<maven path>
+//! assets/indexes/<asset index id>.json
+//! assets/objects/<first two characters of hash>/<hash>
+//! assets/virtual/<asset index id>/<name>
+//! assets/log_configs/<file>
+//! resources/<name>
+//! runtime/<component>/<platform>/<component>/
+//! ```
+//!
+//! # Example
+//! ```
+//! use piston_mc::game_directory::GameDirectory;
+//! use std::path::Path;
+//!
+//! let directory = GameDirectory::new(".minecraft");
+//! assert_eq!(directory.client_jar("1.20.1"), Path::new(".minecraft/versions/1.20.1/1.20.1.jar"));
+//! assert_eq!(directory.asset_index("5"), Path::new(".minecraft/assets/indexes/5.json"));
+//! ```
+
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameDirectory {
+    root: PathBuf,
+}
+
+impl GameDirectory {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self { root: root.as_ref().to_path_buf() }
+    }
+
+    /// The directory the official launcher uses on this platform, `None` if the home directory is unknown.
+    pub fn official() -> Option<Self> {
+        let root = if cfg!(target_os = "windows") {
+            PathBuf::from(std::env::var_os("APPDATA")?).join(".minecraft")
+        } else if cfg!(target_os = "macos") {
+            PathBuf::from(std::env::var_os("HOME")?).join("Library").join("Application Support").join("minecraft")
+        } else {
+            PathBuf::from(std::env::var_os("HOME")?).join(".minecraft")
+        };
+        Some(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn versions(&self) -> PathBuf {
+        self.root.join("versions")
+    }
+
+    pub fn version(&self, id: impl AsRef<str>) -> PathBuf {
+        self.versions().join(id.as_ref())
+    }
+
+    pub fn version_manifest(&self, id: impl AsRef<str>) -> PathBuf {
+        let id = id.as_ref();
+        self.version(id).join(format!("{}.json", id))
+    }
+
+    pub fn client_jar(&self, id: impl AsRef<str>) -> PathBuf {
+        let id = id.as_ref();
+        self.version(id).join(format!("{}.jar", id))
+    }
+
+    /// Where the natives of pre-1.19 versions are extracted to.
+    pub fn natives(&self, id: impl AsRef<str>) -> PathBuf {
+        self.version(id).join("natives")
+    }
+
+    pub fn libraries(&self) -> PathBuf {
+        self.root.join("libraries")
+    }
+
+    pub fn assets(&self) -> PathBuf {
+        self.root.join("assets")
+    }
+
+    pub fn asset_indexes(&self) -> PathBuf {
+        self.assets().join("indexes")
+    }
+
+    pub fn asset_index(&self, id: impl AsRef<str>) -> PathBuf {
+        self.asset_indexes().join(format!("{}.json", id.as_ref()))
+    }
+
+    pub fn asset_objects(&self) -> PathBuf {
+        self.assets().join("objects")
+    }
+
+    /// Where the `legacy` asset index is reconstructed by name, see `Assets::reconstruct`.
+    pub fn virtual_assets(&self, id: impl AsRef<str>) -> PathBuf {
+        self.assets().join("virtual").join(id.as_ref())
+    }
+
+    /// Where the `pre-1.6` asset index is reconstructed by name, versions before 1.6 read their resources from here.
+    pub fn resources(&self) -> PathBuf {
+        self.root.join("resources")
+    }
+
+    pub fn log_configs(&self) -> PathBuf {
+        self.assets().join("log_configs")
+    }
+
+    /// The directory java runtimes are installed to, see [`GameDirectory::runtime`].
+    pub fn runtimes(&self) -> PathBuf {
+        self.root.join("runtime")
+    }
+
+    /// The directory a java runtime `component` such as `java-runtime-gamma` is installed to for `platform`, e.g. `linux`.
+    pub fn runtime(&self, component: impl AsRef<str>, platform: impl AsRef<str>) -> PathBuf {
+        let component = component.as_ref();
+        self.runtimes().join(component).join(platform.as_ref()).join(component)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game_directory::GameDirectory;
+    use std::path::Path;
+
+    #[test]
+    fn layout() {
+        let directory = GameDirectory::new(".minecraft");
+        assert_eq!(directory.version_manifest("1.20.1"), Path::new(".minecraft/versions/1.20.1/1.20.1.json"));
+        assert_eq!(directory.natives("1.12.2"), Path::new(".minecraft/versions/1.12.2/natives"));
+        assert_eq!(directory.libraries(), Path::new(".minecraft/libraries"));
+        assert_eq!(directory.asset_objects(), Path::new(".minecraft/assets/objects"));
+        assert_eq!(directory.virtual_assets("legacy"), Path::new(".minecraft/assets/virtual/legacy"));
+        assert_eq!(directory.resources(), Path::new(".minecraft/resources"));
+        assert_eq!(directory.log_configs(), Path::new(".minecraft/assets/log_configs"));
+        assert_eq!(directory.runtime("java-runtime-gamma", "linux"), Path::new(".minecraft/runtime/java-runtime-gamma/linux/java-runtime-gamma"));
+    }
+}
@@ -0,0 +1,40 @@
+//! HTTP helpers shared by the modules that fetch JSON documents.
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+
+/// Fetches `url` and deserializes the response body as `T`.
+///
+/// Deserialization errors are logged with the line, column and surrounding text of the
+/// offending JSON, which makes changes to Mojang's or a mod loader's schema easy to spot.
+pub(crate) async fn fetch_json<T: DeserializeOwned>(url: &str) -> Result<T> {
+    let response = reqwest::get(url).await?.error_for_status()?;
+    let text = response.text().await?;
+    parse_json(url, &text)
+}
+
+/// Deserializes `text`, which was loaded from `source`, as `T`.
+pub(crate) fn parse_json<T: DeserializeOwned>(source: &str, text: &str) -> Result<T> {
+    let json_result = serde_json::from_str::<T>(text);
+
+    #[cfg(feature = "log")]
+    if let Err(ref e) = json_result {
+        let line = e.line();
+        let column = e.column();
+        let type_name = std::any::type_name::<T>().rsplit("::").next().unwrap_or_default();
+        error!("Failed to deserialize {} from {}: {}", type_name, source, e);
+        error!("Error at line {}, column {}", line, column);
+
+        // Show context around the error (60 chars before and after)
+        let error_offset = text.lines().take(line.saturating_sub(1)).map(|l| l.len() + 1).sum::<usize>() + column.saturating_sub(1);
+        let start = error_offset.saturating_sub(60);
+        let end = (error_offset + 60).min(text.len());
+        let context = text.get(start..end).unwrap_or_default();
+
+        error!("Context around error: {}", context);
+    }
+    #[cfg(not(feature = "log"))]
+    let _ = source;
+
+    Ok(json_result?)
+}
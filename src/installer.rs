@@ -0,0 +1,256 @@
+//! Installs everything a version needs to launch in one call.
+//!
+//! [`Installer::install`] collects the client jar, libraries, assets, logging configuration and
+//! java runtime of a version into a single download batch, so every file shares the same
+//! parallelism budget and progress stream. Files that are already present with the expected
+//! sha1 are left alone, which makes running the installation again cheap.
+//!
+//! # Example
+//! ```no_run
+//! use piston_mc::game_directory::GameDirectory;
+//! use piston_mc::installer::Installer;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let installation = Installer::new("1.20.1", GameDirectory::new(".minecraft")).parallel(20).install(None).await.unwrap();
+//!     println!("Installed {}", installation.version.id);
+//! }
+//! ```
+
+use crate::assets::Assets;
+use crate::game_directory::GameDirectory;
+use crate::java::{self, JavaInstallationFile, JavaManifest, Platform, finalize_files, write_metadata};
+use crate::manifest_v2::ManifestV2;
+use crate::rules::RuleEnvironment;
+use crate::sha_validation::validate_file;
+use crate::version_manifest::VersionManifest;
+use anyhow::{Result, anyhow};
+use simple_download_utility::{FileDownloadArguments, MultiDownloadProgress, download_multiple_files};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Installs a version into a [`GameDirectory`].
+#[derive(Clone, Debug)]
+pub struct Installer {
+    version_id: String,
+    game_directory: GameDirectory,
+    parallel: u16,
+    java: bool,
+}
+
+/// What [`Installer::install`] installed.
+#[derive(Clone, Debug)]
+pub struct Installation {
+    /// The resolved manifest of the installed version.
+    pub version: VersionManifest,
+    /// Directory of the installed java runtime, `None` if java was not installed.
+    pub java_directory: Option<PathBuf>,
+    /// The logging JVM argument pointing at the downloaded log configuration, if the version has one.
+    pub logging_argument: Option<String>,
+}
+
+/// A java runtime selected for installation together with the files it consists of.
+struct JavaInstallation {
+    directory: PathBuf,
+    version: String,
+    files: Vec<JavaInstallationFile>,
+}
+
+impl Installer {
+    pub fn new(version_id: impl Into<String>, game_directory: GameDirectory) -> Self {
+        Self { version_id: version_id.into(), game_directory, parallel: 20, java: true }
+    }
+
+    /// Sets how many files are downloaded at the same time, 20 by default.
+    pub fn parallel(mut self, parallel: u16) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Sets whether the java runtime of the version is installed, `true` by default.
+    pub fn java(mut self, java: bool) -> Self {
+        self.java = java;
+        self
+    }
+
+    /// Installs the version, reporting the progress of all downloads through `sender`.
+    pub async fn install(&self, sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>) -> Result<Installation> {
+        let manifest = ManifestV2::fetch().await?;
+        let version = manifest.version(&self.version_id).await?.ok_or_else(|| anyhow!("Version '{}' not found", self.version_id))?;
+        let version = version.resolve(&manifest).await?;
+        let environment = RuleEnvironment::current();
+
+        let directory = &self.game_directory;
+        version.write_manifest(directory).await?;
+
+        let assets = match &version.asset_index {
+            Some(_) => {
+                let assets = version.assets().await?;
+                assets.write_index(directory).await?;
+                Some(assets)
+            }
+            None => None,
+        };
+
+        let java = match self.java {
+            true => Some(self.java_installation(&version).await?),
+            false => None,
+        };
+
+        let download_items = self.download_items(&version, assets.as_ref(), java.as_ref(), &environment);
+        let download_items = tokio::task::spawn_blocking(move || pending(download_items)).await?;
+        info!("Downloading {} files for {}", download_items.len(), version.id);
+        download_multiple_files(download_items, self.parallel, sender).await?;
+
+        if let Some(assets) = &assets {
+            assets.reconstruct(directory).await?;
+        }
+        if let Some(java) = &java {
+            finalize_files(&java.directory, &java.files).await?;
+            write_metadata(&java.directory, &java.version, &java.files).await?;
+        }
+        version.extract_natives(directory.libraries(), directory.natives(&version.id), &environment).await?;
+
+        let logging_argument = version.logging.as_ref().and_then(|logging| {
+            let name = logging.client.file.id.as_ref()?;
+            Some(logging.client.jvm_argument(directory.log_configs().join(name)))
+        });
+
+        Ok(Installation { java_directory: java.map(|java| java.directory), logging_argument, version })
+    }
+
+    /// Collects every file of the installation, whether or not it is already present.
+    fn download_items(
+        &self,
+        version: &VersionManifest,
+        assets: Option<&Assets>,
+        java: Option<&JavaInstallation>,
+        environment: &RuleEnvironment,
+    ) -> Vec<FileDownloadArguments> {
+        let mut download_items = Vec::new();
+
+        if let Some(downloads) = &version.downloads {
+            download_items.push(FileDownloadArguments {
+                url: downloads.client.url.clone(),
+                sha1: Some(downloads.client.sha1.clone()),
+                sender: None,
+                path: self.game_directory.client_jar(&version.id).to_string_lossy().into_owned(),
+            });
+        }
+
+        download_items.extend(version.library_downloads(&self.game_directory.libraries(), environment));
+
+        if let Some(assets) = assets {
+            download_items.extend(assets.download_items(&self.game_directory.asset_objects()));
+        }
+
+        if let Some(file) = version.logging.as_ref().map(|logging| &logging.client.file)
+            && let Some(name) = &file.id
+        {
+            download_items.push(FileDownloadArguments {
+                url: file.url.clone(),
+                sha1: Some(file.sha1.clone()),
+                sender: None,
+                path: self.game_directory.log_configs().join(name).to_string_lossy().into_owned(),
+            });
+        }
+
+        if let Some(java) = java {
+            download_items.extend(java::download_items(&java.directory, &java.files));
+        }
+
+        download_items
+    }
+
+    /// Picks the java runtime `version` asks for on the current platform, `jre-legacy` if it does not say.
+    async fn java_installation(&self, version: &VersionManifest) -> Result<JavaInstallation> {
+        let java_version = version.java_version.clone().unwrap_or_default();
+        let platform = Platform::current()
+            .ok_or_else(|| anyhow!("Java runtimes are not available for {}-{}", std::env::consts::OS, std::env::consts::ARCH))?;
+        let manifest = JavaManifest::fetch().await?;
+        let runtime = manifest
+            .runtime_for(&java_version, platform)
+            .ok_or_else(|| anyhow!("Java runtime '{}' is not available for {}", java_version.component, platform))?;
+        debug!("Installing java {} ({}) for {}", runtime, java_version.component, version.id);
+
+        Ok(JavaInstallation {
+            directory: self.game_directory.runtime(&java_version.component, platform.name()),
+            version: runtime.to_string(),
+            files: runtime.get_installation_files().await?,
+        })
+    }
+}
+
+/// Drops the files that are listed twice or already present with the expected sha1.
+fn pending(download_items: Vec<FileDownloadArguments>) -> Vec<FileDownloadArguments> {
+    let mut paths = HashSet::new();
+    download_items
+        .into_iter()
+        .filter(|item| paths.insert(item.path.clone()))
+        .filter(|item| !item.sha1.as_ref().is_some_and(|sha1| validate_file(&item.path, sha1)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::installer::*;
+    use std::path::Path;
+
+    const MANIFEST: &str = r#"{
+        "id": "1.20.1",
+        "mainClass": "net.minecraft.client.main.Main",
+        "releaseTime": "2023-06-12T13:25:51+00:00",
+        "time": "2023-06-12T13:25:51+00:00",
+        "type": "release",
+        "downloads": { "client": { "sha1": "da39a3ee5e6b4b0d3255bfef95601890afd80709", "size": 0, "url": "https://piston-data.mojang.com/client.jar" } },
+        "logging": {
+            "client": {
+                "argument": "-Dlog4j.configurationFile=${path}",
+                "type": "log4j2-xml",
+                "file": { "id": "client-1.12.xml", "sha1": "bd65e7d2e3c237be76cfbef4c2405033d7f91521", "size": 888, "url": "https://piston-data.mojang.com/client-1.12.xml" }
+            }
+        },
+        "libraries": [
+            {
+                "name": "com.mojang:patchy:1.1",
+                "downloads": { "artifact": { "path": "com/mojang/patchy/1.1/patchy-1.1.jar", "sha1": "aef6", "size": 15817, "url": "https://libraries.minecraft.net/com/mojang/patchy/1.1/patchy-1.1.jar" } }
+            },
+            {
+                "name": "com.mojang:patchy:1.1",
+                "downloads": { "artifact": { "path": "com/mojang/patchy/1.1/patchy-1.1.jar", "sha1": "aef6", "size": 15817, "url": "https://libraries.minecraft.net/com/mojang/patchy/1.1/patchy-1.1.jar" } }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn download_items() {
+        let root = Path::new("target/test/installer");
+        _ = std::fs::remove_dir_all(root);
+        let installer = Installer::new("1.20.1", GameDirectory::new(root));
+        let version: VersionManifest = serde_json::from_str(MANIFEST).unwrap();
+        let assets: Assets = serde_json::from_str(
+            r#"{ "url": "", "asset_id": "5", "objects": { "icons/icon_16x16.png": { "hash": "bdf48ef6b5d0d23bbb02e17d04865216179f510a", "size": 3665 } } }"#,
+        )
+        .unwrap();
+
+        let items = installer.download_items(&version, Some(&assets), None, &RuleEnvironment::new("linux", "x86_64"));
+        let paths: Vec<_> = items.iter().map(|item| item.path.clone()).collect();
+        assert_eq!(
+            paths,
+            [
+                root.join("versions/1.20.1/1.20.1.jar"),
+                root.join("libraries/com/mojang/patchy/1.1/patchy-1.1.jar"),
+                root.join("assets/objects/bd/bdf48ef6b5d0d23bbb02e17d04865216179f510a"),
+                root.join("assets/log_configs/client-1.12.xml"),
+            ]
+            .map(|path| path.to_string_lossy().into_owned())
+        );
+
+        // The client jar fixture is the empty file, once it is present it must not be downloaded again.
+        std::fs::create_dir_all(root.join("versions/1.20.1")).unwrap();
+        std::fs::write(root.join("versions/1.20.1/1.20.1.jar"), b"").unwrap();
+        let pending = pending(items);
+        assert_eq!(pending.len(), 3);
+        assert!(pending.iter().all(|item| !item.path.ends_with("1.20.1.jar")));
+    }
+}
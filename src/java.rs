@@ -1,49 +1,57 @@
 #![doc = include_str!("../.wiki/Java.md")]
 
+use crate::game_directory::GameDirectory;
+use crate::http::fetch_json;
+use crate::version_manifest::JavaVersion;
 use simple_download_utility::{FileDownloadArguments, MultiDownloadProgress, download_multiple_files};
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use crate::sha_validation::validate_file;
+use futures_util::stream::{self, StreamExt};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 const PISTON_URL: &str = "https://piston-meta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
 
+/// The runtimes Mojang publishes, keyed by platform name.
+///
+/// Platforms this crate does not know yet are kept in [`JavaManifest::platforms`], use
+/// [`JavaManifest::runtimes`] for the ones in [`Platform`].
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct JavaManifest {
-    pub linux: Runtimes,
+    pub platforms: HashMap<String, Runtimes>,
+}
+
+/// The platforms Mojang publishes java runtimes for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Platform {
+    #[serde(rename = "linux")]
+    Linux,
     #[serde(rename = "linux-i386")]
-    pub linux_i386: Runtimes,
+    LinuxI386,
     #[serde(rename = "mac-os")]
-    pub macos: Runtimes,
+    MacOs,
     #[serde(rename = "mac-os-arm64")]
-    pub macos_arm64: Runtimes,
+    MacOsArm64,
     #[serde(rename = "windows-arm64")]
-    pub windows_arm64: Runtimes,
+    WindowsArm64,
     #[serde(rename = "windows-x64")]
-    pub windows_x64: Runtimes,
+    WindowsX64,
     #[serde(rename = "windows-x86")]
-    pub windows_x86: Runtimes,
+    WindowsX86,
 }
 
+/// The runtimes of one platform, keyed by component name such as `java-runtime-gamma`.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Runtimes {
-    #[serde(rename = "java-runtime-alpha")]
-    pub alpha: Vec<JavaRuntime>,
-    #[serde(rename = "java-runtime-beta")]
-    pub beta: Vec<JavaRuntime>,
-    #[serde(rename = "java-runtime-gamma")]
-    pub gamma: Vec<JavaRuntime>,
-    #[serde(rename = "java-runtime-delta")]
-    pub delta: Vec<JavaRuntime>,
-    #[serde(rename = "java-runtime-gamma-snapshot")]
-    pub gamma_snapshot: Vec<JavaRuntime>,
-    #[serde(rename = "java-runtime-epsilon")]
-    pub epsilon: Vec<JavaRuntime>,
-    #[serde(rename = "jre-legacy")]
-    pub legacy: Vec<JavaRuntime>,
-    #[serde(rename = "minecraft-java-exe")]
-    pub minecraft_java_exe: serde_json::Value,
+    /// The native launcher executable published for windows, which is not a java runtime component.
+    #[serde(rename = "minecraft-java-exe", default, skip_serializing_if = "Vec::is_empty")]
+    pub minecraft_java_exe: Vec<JavaRuntime>,
+    /// Every component, including the ones this crate does not know yet.
+    #[serde(flatten)]
+    pub components: HashMap<String, Vec<JavaRuntime>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -72,7 +80,13 @@ pub struct Availability {
     pub progress: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// The file list a runtime's [`Manifest`] points at, keyed by path relative to the runtime directory.
+#[derive(Deserialize)]
+struct RuntimeFiles {
+    files: HashMap<String, JavaInstallationFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JavaInstallationFile {
     #[serde(skip)]
     pub name: String,
@@ -80,21 +94,23 @@ pub struct JavaInstallationFile {
     pub file_type: Option<FileType>,
     pub executable: Option<bool>,
     pub downloads: Option<Downloads>,
+    /// Where a [`FileType::Link`] points to, relative to the directory containing the link.
+    pub target: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Downloads {
     pub lzma: Option<DownloadItem>,
     pub raw: DownloadItem,
 }
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadItem {
     pub sha1: String,
     pub size: usize,
     pub url: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum FileType {
     #[serde(rename = "file")]
     File,
@@ -104,46 +120,170 @@ pub enum FileType {
     Link,
 }
 
+/// A runtime found in the `runtime` directory of a [`GameDirectory`],
+/// identified by the metadata [`JavaRuntime::install_into`] writes next to it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InstalledRuntime {
+    pub component: String,
+    pub platform: Platform,
+    /// Name of the installed runtime version, e.g. `17.0.8`.
+    pub version: String,
+    /// The directory containing the runtime itself, `<component>/<platform>/<component>`.
+    pub directory: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JavaValidationResult {
+    pub runtime: String,
+    pub succeeded: Vec<String>,
+    pub failed: Vec<JavaValidationFailureResult>,
+    /// Files in the installation directory that are not part of the runtime.
+    pub unexpected: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JavaValidationFailureResult {
+    pub name: String,
+    pub reason: JavaValidationFailureReason,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum JavaValidationFailureReason {
+    FileNotFound,
+    /// A file is where a directory or link belongs, or the other way around.
+    FileTypeNotMatching,
+    SizeNotMatching,
+    HashNotMatching,
+    LinkTargetNotMatching,
+    NotExecutable,
+}
+
 impl JavaManifest {
     pub async fn fetch() -> Result<Self> {
-        let response = reqwest::get(PISTON_URL).await?;
-        let text = response.text().await?;
-        let json_result = serde_json::from_str::<Self>(&text);
-        #[cfg(feature = "log")]
-        if let Err(ref e) = json_result {
-            let line = e.line();
-            let column = e.column();
-            error!("Failed to deserialize VersionManifest from {}: {}", PISTON_URL, e);
-            error!("Error at line {}, column {}", line, column);
-
-            // Show context around the error (60 chars before and after)
-            let error_offset = text.lines().take(line - 1).map(|l| l.len() + 1).sum::<usize>() + column - 1;
-            let start = error_offset.saturating_sub(60);
-            let end = (error_offset + 60).min(text.len());
-            let context = &text[start..end];
-
-            error!("Context around error: {}", context);
+        fetch_json(PISTON_URL).await
+    }
+
+    /// Returns the runtimes of `platform`, or `None` if the manifest does not list the platform.
+    pub fn runtimes(&self, platform: Platform) -> Option<&Runtimes> {
+        self.platforms.get(platform.name())
+    }
+
+    /// Returns the runtime of the component `java_version` names for `platform`, or `None` if
+    /// that component is not published for the platform.
+    ///
+    /// Versions without a `javaVersion` run on [`JavaVersion::default`], the `jre-legacy` component:
+    /// ```no_run
+    /// # use piston_mc::java::{JavaManifest, Platform};
+    /// # use piston_mc::version_manifest::VersionManifest;
+    /// # async fn example(manifest: JavaManifest, version: VersionManifest) {
+    /// let java_version = version.java_version.clone().unwrap_or_default();
+    /// let runtime = manifest.runtime_for(&java_version, Platform::current().unwrap());
+    /// # }
+    /// ```
+    pub fn runtime_for(&self, java_version: &JavaVersion, platform: Platform) -> Option<&JavaRuntime> {
+        self.runtimes(platform)?.component(&java_version.component)?.first()
+    }
+}
+
+impl Platform {
+    pub const ALL: [Platform; 7] = [
+        Platform::Linux,
+        Platform::LinuxI386,
+        Platform::MacOs,
+        Platform::MacOsArm64,
+        Platform::WindowsArm64,
+        Platform::WindowsX64,
+        Platform::WindowsX86,
+    ];
+
+    /// Looks up a platform by the name Mojang uses for it, see [`Platform::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|platform| platform.name() == name)
+    }
+
+    /// The platform this program runs on, or `None` if Mojang does not publish runtimes for it,
+    /// e.g. on linux-aarch64.
+    pub fn current() -> Option<Self> {
+        Self::from_target(std::env::consts::OS, std::env::consts::ARCH)
+    }
+
+    /// Maps a rust target OS and architecture, see [`std::env::consts`], to a platform.
+    fn from_target(os: &str, arch: &str) -> Option<Self> {
+        match (os, arch) {
+            ("windows", "aarch64") => Some(Platform::WindowsArm64),
+            ("windows", "x86") => Some(Platform::WindowsX86),
+            ("windows", "x86_64") => Some(Platform::WindowsX64),
+            ("macos", "aarch64") => Some(Platform::MacOsArm64),
+            ("macos", "x86_64") => Some(Platform::MacOs),
+            ("linux", "x86") => Some(Platform::LinuxI386),
+            ("linux", "x86_64") => Some(Platform::Linux),
+            _ => None,
+        }
+    }
+
+    /// The name Mojang uses for this platform, e.g. `mac-os-arm64`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::Linux => "linux",
+            Platform::LinuxI386 => "linux-i386",
+            Platform::MacOs => "mac-os",
+            Platform::MacOsArm64 => "mac-os-arm64",
+            Platform::WindowsArm64 => "windows-arm64",
+            Platform::WindowsX64 => "windows-x64",
+            Platform::WindowsX86 => "windows-x86",
         }
-        Ok(json_result?)
+    }
+}
+
+impl Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Runtimes {
+    /// Returns the runtimes of `component`, e.g. `java-runtime-gamma`, or `None` if the platform does not list it.
+    pub fn component(&self, component: &str) -> Option<&Vec<JavaRuntime>> {
+        self.components.get(component)
+    }
+
+    pub fn alpha(&self) -> &[JavaRuntime] {
+        self.known_component("java-runtime-alpha")
+    }
+
+    pub fn beta(&self) -> &[JavaRuntime] {
+        self.known_component("java-runtime-beta")
+    }
+
+    pub fn gamma(&self) -> &[JavaRuntime] {
+        self.known_component("java-runtime-gamma")
+    }
+
+    pub fn gamma_snapshot(&self) -> &[JavaRuntime] {
+        self.known_component("java-runtime-gamma-snapshot")
+    }
+
+    pub fn delta(&self) -> &[JavaRuntime] {
+        self.known_component("java-runtime-delta")
+    }
+
+    pub fn epsilon(&self) -> &[JavaRuntime] {
+        self.known_component("java-runtime-epsilon")
+    }
+
+    pub fn legacy(&self) -> &[JavaRuntime] {
+        self.known_component("jre-legacy")
+    }
+
+    fn known_component(&self, component: &str) -> &[JavaRuntime] {
+        self.component(component).map(Vec::as_slice).unwrap_or_default()
     }
 }
 
 impl JavaRuntime {
     pub async fn get_installation_files(&self) -> Result<Vec<JavaInstallationFile>> {
-        let url = self.manifest.url.clone();
-        let response = reqwest::get(&url).await?;
-        let files: serde_json::Value = response.json().await?;
-        let files = files.get("files").ok_or_else(|| anyhow!("Missing 'files' field in response"))?;
-        let json_result = serde_json::from_value::<HashMap<String, JavaInstallationFile>>(files.clone());
-        #[cfg(feature = "log")]
-        if let Err(ref e) = json_result {
-            let line = e.line();
-            let column = e.column();
-            error!("Failed to deserialize VersionManifest from {}: {}", url, e);
-            error!("Error at line {}, column {}", line, column);
-        }
-        let map = json_result?;
-        Ok(map
+        let RuntimeFiles { files } = fetch_json(&self.manifest.url).await?;
+        Ok(files
             .into_iter()
             .map(|(name, mut file)| {
                 file.name = name;
@@ -152,36 +292,448 @@ impl JavaRuntime {
             .collect())
     }
 
+    /// Installs the runtime into `directory`, creating its links, directories and executable bits.
+    ///
+    /// Only `directory` is written to, so the `.version` and `<component>.sha1` metadata is skipped
+    /// and [`InstalledRuntime::list`] and [`InstalledRuntime::update`] do not see the runtime. Use
+    /// [`JavaRuntime::install_into`] to install into a [`GameDirectory`] together with its metadata.
     pub async fn install(
         &self,
         directory: impl AsRef<Path>,
         parallel: u16,
         sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
     ) -> Result<()> {
-        let directory = directory.as_ref();
         let installation_files = self.get_installation_files().await?;
+        install_files(directory.as_ref(), &installation_files, parallel, sender).await
+    }
+
+    /// Installs the runtime as `component` into [`GameDirectory::runtime`] like the official launcher,
+    /// together with the `.version` and `<component>.sha1` files [`InstalledRuntime::list`] reads.
+    ///
+    /// Returns the directory the runtime was installed to.
+    pub async fn install_into(
+        &self,
+        game_directory: &GameDirectory,
+        component: &str,
+        platform: Platform,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<PathBuf> {
+        let directory = game_directory.runtime(component, platform.name());
+        let installation_files = self.get_installation_files().await?;
+        install_files(&directory, &installation_files, parallel, sender).await?;
+        write_metadata(&directory, &self.version.name, &installation_files).await?;
+        Ok(directory)
+    }
+
+    /// Checks the runtime installed in `directory`: the size and sha1 of every file, link targets,
+    /// directories and executable bits, and whether files that are not part of the runtime are present.
+    pub async fn verify(&self, directory: impl AsRef<Path>, parallel: u16) -> Result<JavaValidationResult> {
+        let installation_files = self.get_installation_files().await?;
+        verify_files(self.to_string(), directory.as_ref(), &installation_files, parallel).await
+    }
+
+    /// Verifies the runtime installed in `directory`, downloads only the broken files again and
+    /// removes the files that are not part of the runtime.
+    ///
+    /// Returns the result of the verification the repair was based on. Like [`JavaRuntime::install`]
+    /// this skips the runtime metadata, see [`JavaRuntime::repair_into`].
+    pub async fn repair(
+        &self,
+        directory: impl AsRef<Path>,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<JavaValidationResult> {
+        let installation_files = self.get_installation_files().await?;
+        self.repair_installation(directory.as_ref(), &installation_files, parallel, sender).await
+    }
+
+    /// Repairs the runtime installed with [`JavaRuntime::install_into`] and updates its metadata.
+    pub async fn repair_into(
+        &self,
+        game_directory: &GameDirectory,
+        component: &str,
+        platform: Platform,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<JavaValidationResult> {
+        let directory = game_directory.runtime(component, platform.name());
+        let installation_files = self.get_installation_files().await?;
+        let result = self.repair_installation(&directory, &installation_files, parallel, sender).await?;
+        write_metadata(&directory, &self.version.name, &installation_files).await?;
+        Ok(result)
+    }
+
+    async fn repair_installation(
+        &self,
+        directory: &Path,
+        installation_files: &[JavaInstallationFile],
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<JavaValidationResult> {
+        let result = verify_files(self.to_string(), directory, installation_files, parallel).await?;
+        if !result.is_valid() {
+            repair_files(directory, installation_files, &result, parallel, sender).await?;
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(feature = "lzma")]
+impl JavaRuntime {
+    /// Installs the runtime like [`JavaRuntime::install`], but downloads the lzma compressed
+    /// variant of every file that has one. Files are decompressed while they download and
+    /// validated against the sha1 of the uncompressed file.
+    ///
+    /// Like [`JavaRuntime::install`] this skips the runtime metadata, see [`JavaRuntime::install_lzma_into`].
+    pub async fn install_lzma(
+        &self,
+        directory: impl AsRef<Path>,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<()> {
+        let installation_files = self.get_installation_files().await?;
+        install_lzma_files(directory.as_ref(), &installation_files, parallel, sender).await
+    }
+
+    /// Installs the runtime like [`JavaRuntime::install_into`], downloading the lzma compressed variants.
+    pub async fn install_lzma_into(
+        &self,
+        game_directory: &GameDirectory,
+        component: &str,
+        platform: Platform,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<PathBuf> {
+        let directory = game_directory.runtime(component, platform.name());
+        let installation_files = self.get_installation_files().await?;
+        install_lzma_files(&directory, &installation_files, parallel, sender).await?;
+        write_metadata(&directory, &self.version.name, &installation_files).await?;
+        Ok(directory)
+    }
+}
+
+async fn install_files(
+    directory: &Path,
+    installation_files: &[JavaInstallationFile],
+    parallel: u16,
+    sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+) -> Result<()> {
+    #[cfg(feature = "log")]
+    info!("Downloading files: {:?}", installation_files);
 
-        let args: Vec<FileDownloadArguments> = installation_files
-            .iter()
-            .filter_map(|item| {
-                item.downloads.as_ref().map(|download| FileDownloadArguments {
-                    url: download.raw.url.clone(),
-                    path: directory.join(&item.name).to_string_lossy().to_string(),
-                    sender: None,
-                    sha1: Some(download.raw.sha1.clone()),
-                })
+    download_multiple_files(download_items(directory, installation_files), parallel, sender).await?;
+    finalize_files(directory, installation_files).await?;
+
+    Ok(())
+}
+
+/// The download of every regular file of an installation into `directory`.
+pub(crate) fn download_items(directory: &Path, installation_files: &[JavaInstallationFile]) -> Vec<FileDownloadArguments> {
+    installation_files
+        .iter()
+        .filter_map(|item| {
+            item.downloads.as_ref().map(|download| FileDownloadArguments {
+                url: download.raw.url.clone(),
+                path: directory.join(&item.name).to_string_lossy().to_string(),
+                sender: None,
+                sha1: Some(download.raw.sha1.clone()),
             })
-            .collect();
+        })
+        .collect()
+}
 
-        #[cfg(feature = "log")]
-        info!("Downloading files: {:?}", installation_files);
+#[cfg(feature = "lzma")]
+async fn install_lzma_files(
+    directory: &Path,
+    installation_files: &[JavaInstallationFile],
+    parallel: u16,
+    sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+) -> Result<()> {
+    let downloads = installation_files
+        .iter()
+        .filter_map(|item| item.downloads.as_ref().map(|downloads| (directory.join(&item.name), downloads.clone())))
+        .collect();
+
+    crate::lzma::download_files(downloads, parallel, sender).await?;
+    finalize_files(directory, installation_files).await?;
 
-        download_multiple_files(args, parallel, sender).await?;
+    Ok(())
+}
+
+/// Creates the directories and links of an installation and marks its executables as such,
+/// once the files themselves are downloaded into `directory`.
+pub(crate) async fn finalize_files(directory: &Path, files: &[JavaInstallationFile]) -> Result<()> {
+    for file in files {
+        let path = directory.join(&file.name);
+        match file.file_type {
+            Some(FileType::Directory) => tokio::fs::create_dir_all(&path).await?,
+            Some(FileType::Link) => {
+                let target = file.target.as_ref().ok_or_else(|| anyhow!("Link '{}' has no target", file.name))?;
+                create_link(&path, target).await?;
+            }
+            Some(FileType::File) | None => {
+                if file.executable == Some(true) {
+                    set_executable(&path).await?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
 
+#[cfg(unix)]
+async fn create_link(path: &Path, target: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    if tokio::fs::symlink_metadata(path).await.is_ok() {
+        tokio::fs::remove_file(path).await?;
+    }
+    tokio::fs::symlink(target, path).await?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn create_link(_path: &Path, _target: &str) -> Result<()> {
+    // Runtimes for windows do not contain links, and creating one there needs extra privileges.
+    debug!("Skipping link {} -> {}", _path.display(), _target);
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn set_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).await?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn set_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+impl InstalledRuntime {
+    /// Lists the runtimes installed below `runtime_directory`, usually [`GameDirectory::runtimes`](crate::game_directory::GameDirectory::runtimes).
+    ///
+    /// Only runtimes with a `.version` file are listed, as written by [`JavaRuntime::install_into`].
+    /// Directories of unknown platforms are skipped.
+    pub async fn list(runtime_directory: impl AsRef<Path>) -> Result<Vec<InstalledRuntime>> {
+        let mut runtimes = vec![];
+        let Ok(mut components) = tokio::fs::read_dir(runtime_directory.as_ref()).await else {
+            return Ok(runtimes);
+        };
+        while let Some(component) = components.next_entry().await? {
+            if !component.file_type().await?.is_dir() {
+                continue;
+            }
+            let component_name = component.file_name().to_string_lossy().into_owned();
+            let mut platforms = tokio::fs::read_dir(component.path()).await?;
+            while let Some(platform) = platforms.next_entry().await? {
+                let Some(platform_name) = Platform::from_name(&platform.file_name().to_string_lossy()) else {
+                    continue;
+                };
+                let Ok(version) = tokio::fs::read_to_string(platform.path().join(".version")).await else {
+                    continue;
+                };
+                runtimes.push(InstalledRuntime {
+                    component: component_name.clone(),
+                    platform: platform_name,
+                    version: version.trim().to_string(),
+                    directory: platform.path().join(&component_name),
+                });
+            }
+        }
+        runtimes.sort_by(|a, b| (&a.component, a.platform.name()).cmp(&(&b.component, b.platform.name())));
+        Ok(runtimes)
+    }
+
+    /// Returns the runtime this installation should be updated to, or `None` if it is up to date
+    /// or its component is no longer published.
+    pub fn update<'a>(&self, manifest: &'a JavaManifest) -> Option<&'a JavaRuntime> {
+        let runtime = manifest.runtimes(self.platform)?.component(&self.component)?.first()?;
+        (runtime.version.name != self.version).then_some(runtime)
+    }
+
+    /// Deletes the runtime together with its metadata.
+    pub async fn uninstall(self) -> Result<()> {
+        let platform_directory = self.directory.parent().ok_or_else(|| anyhow!("Invalid runtime directory {}", self.directory.display()))?;
+        debug!("Uninstalling {} {} from {}", self.component, self.version, platform_directory.display());
+        tokio::fs::remove_dir_all(platform_directory).await?;
+        if let Some(component_directory) = platform_directory.parent() {
+            // Only removed once no other platform of the component is left.
+            _ = tokio::fs::remove_dir(component_directory).await;
+        }
         Ok(())
     }
 }
 
+/// Writes the metadata the official launcher keeps next to a runtime installed in
+/// `<component>/<platform>/<component>`: the version name in `.version`, and the sha1 of
+/// every file in `<component>.sha1`.
+pub(crate) async fn write_metadata(directory: &Path, version: &str, files: &[JavaInstallationFile]) -> Result<()> {
+    let (Some(component), Some(platform_directory)) = (directory.file_name(), directory.parent()) else {
+        return Err(anyhow!("Invalid runtime directory {}", directory.display()));
+    };
+    let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_nanos();
+    let sha1: String = files
+        .iter()
+        .filter_map(|file| file.downloads.as_ref().map(|downloads| format!("{} /#// {} {}\n", file.name, downloads.raw.sha1, timestamp)))
+        .collect();
+
+    tokio::fs::write(platform_directory.join(".version"), version).await?;
+    tokio::fs::write(platform_directory.join(format!("{}.sha1", component.to_string_lossy())), sha1).await?;
+    Ok(())
+}
+
+impl JavaValidationResult {
+    pub fn is_valid(&self) -> bool {
+        self.failed.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+/// Checks every file of an installation in `directory` and looks for files that do not belong to it.
+async fn verify_files(runtime: String, directory: &Path, files: &[JavaInstallationFile], parallel: u16) -> Result<JavaValidationResult> {
+    let results: Vec<_> = stream::iter(files.iter().cloned())
+        .map(|file| {
+            let directory = directory.to_path_buf();
+            tokio::task::spawn_blocking(move || {
+                let validation = verify_file(&directory, &file);
+                (file.name, validation)
+            })
+        })
+        .buffer_unordered(parallel as usize)
+        .collect()
+        .await;
+
+    let mut result = JavaValidationResult { runtime, succeeded: vec![], failed: vec![], unexpected: vec![] };
+    for join_result in results {
+        let (name, validation) = join_result?;
+        match validation {
+            Ok(_) => result.succeeded.push(name),
+            Err(reason) => result.failed.push(JavaValidationFailureResult { name, reason }),
+        }
+    }
+
+    let expected: HashSet<String> = files.iter().map(|file| file.name.clone()).collect();
+    let installed_directory = directory.to_path_buf();
+    let installed = tokio::task::spawn_blocking(move || list_files(&installed_directory, "")).await??;
+    result.unexpected = installed.into_iter().filter(|name| !expected.contains(name)).collect();
+    result.unexpected.sort();
+
+    Ok(result)
+}
+
+fn verify_file(directory: &Path, file: &JavaInstallationFile) -> Result<(), JavaValidationFailureReason> {
+    if matches!(file.file_type, Some(FileType::Link)) && cfg!(not(unix)) {
+        // Links are not created on windows, see `create_link`.
+        return Ok(());
+    }
+
+    let path = directory.join(&file.name);
+    let metadata = std::fs::symlink_metadata(&path).map_err(|_| JavaValidationFailureReason::FileNotFound)?;
+    match file.file_type {
+        Some(FileType::Directory) if !metadata.is_dir() => Err(JavaValidationFailureReason::FileTypeNotMatching),
+        Some(FileType::Directory) => Ok(()),
+        Some(FileType::Link) if !metadata.is_symlink() => Err(JavaValidationFailureReason::FileTypeNotMatching),
+        Some(FileType::Link) => match std::fs::read_link(&path) {
+            Ok(target) if file.target.as_ref().is_some_and(|expected| target == Path::new(expected)) => Ok(()),
+            _ => Err(JavaValidationFailureReason::LinkTargetNotMatching),
+        },
+        Some(FileType::File) | None => {
+            if !metadata.is_file() {
+                return Err(JavaValidationFailureReason::FileTypeNotMatching);
+            }
+            if let Some(downloads) = &file.downloads {
+                if metadata.len() != downloads.raw.size as u64 {
+                    return Err(JavaValidationFailureReason::SizeNotMatching);
+                }
+                if !validate_file(&path, &downloads.raw.sha1) {
+                    return Err(JavaValidationFailureReason::HashNotMatching);
+                }
+            }
+            if file.executable == Some(true) && !is_executable(&metadata) {
+                return Err(JavaValidationFailureReason::NotExecutable);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Lists the files and links below `directory`, named like the entries of the runtime manifest.
+fn list_files(directory: &Path, prefix: &str) -> Result<Vec<String>> {
+    let mut files = vec![];
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return Ok(files);
+    };
+    for entry in entries {
+        let entry = entry?;
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        if entry.file_type()?.is_dir() {
+            files.extend(list_files(&entry.path(), &format!("{}/", name))?);
+        } else {
+            files.push(name);
+        }
+    }
+    Ok(files)
+}
+
+/// Removes the unexpected files of `result` and installs its failed entries again.
+async fn repair_files(
+    directory: &Path,
+    files: &[JavaInstallationFile],
+    result: &JavaValidationResult,
+    parallel: u16,
+    sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+) -> Result<()> {
+    for name in &result.unexpected {
+        debug!("Removing {} from {}", name, directory.display());
+        tokio::fs::remove_file(directory.join(name)).await?;
+    }
+
+    let failed: HashMap<&str, &JavaValidationFailureReason> = result.failed.iter().map(|failure| (failure.name.as_str(), &failure.reason)).collect();
+    let mut broken = vec![];
+    let mut download_items = vec![];
+    for file in files {
+        let Some(reason) = failed.get(file.name.as_str()) else {
+            continue;
+        };
+        let path = directory.join(&file.name);
+        if **reason == JavaValidationFailureReason::FileTypeNotMatching {
+            match tokio::fs::symlink_metadata(&path).await?.is_dir() {
+                true => tokio::fs::remove_dir_all(&path).await?,
+                false => tokio::fs::remove_file(&path).await?,
+            }
+        }
+        if let Some(downloads) = &file.downloads
+            && **reason != JavaValidationFailureReason::NotExecutable
+        {
+            download_items.push(FileDownloadArguments {
+                url: downloads.raw.url.clone(),
+                path: path.to_string_lossy().into_owned(),
+                sender: None,
+                sha1: Some(downloads.raw.sha1.clone()),
+            });
+        }
+        broken.push(file.clone());
+    }
+
+    debug!("Repairing {} files in {}", broken.len(), directory.display());
+    download_multiple_files(download_items, parallel, sender).await?;
+    finalize_files(directory, &broken).await
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    true
+}
+
 impl Display for JavaRuntime {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.version.name)
@@ -190,10 +742,11 @@ impl Display for JavaRuntime {
 
 #[cfg(test)]
 mod test {
-    use crate::java::JavaManifest;
+    use crate::java::*;
+    use crate::serve_test_responses;
+    use crate::version_manifest::JavaVersion;
     #[cfg(feature = "log")]
     use crate::setup_logging;
-    use futures_util::{StreamExt, stream};
 
     #[tokio::test]
     async fn fetch() {
@@ -202,62 +755,204 @@ mod test {
         let manifest = JavaManifest::fetch().await.unwrap();
         info!("{:?}", manifest);
     }
+    /// A manifest publishing `java-runtime-gamma` 17.0.8 and `jre-legacy` 8u51 for linux only,
+    /// plus a component and a platform this crate does not know.
+    fn test_manifest() -> JavaManifest {
+        let runtime = |name: &str| {
+            serde_json::json!([{
+                "availability": { "group": 1, "progress": 100 },
+                "manifest": { "sha1": "abc", "size": 1, "url": "https://piston-meta.mojang.com/manifest.json" },
+                "version": { "name": name, "released": "2023-01-01T00:00:00+00:00" }
+            }])
+        };
+        let components = serde_json::json!({
+            "java-runtime-alpha": [], "java-runtime-beta": [], "java-runtime-gamma": runtime("17.0.8"), "java-runtime-delta": [],
+            "java-runtime-gamma-snapshot": [], "java-runtime-epsilon": [], "jre-legacy": runtime("8u51"), "minecraft-java-exe": [],
+            "java-runtime-zeta": runtime("25.0.1")
+        });
+        let empty = serde_json::json!({
+            "java-runtime-alpha": [], "java-runtime-beta": [], "java-runtime-gamma": [], "java-runtime-delta": [],
+            "java-runtime-gamma-snapshot": [], "java-runtime-epsilon": [], "jre-legacy": [], "minecraft-java-exe": []
+        });
+        let mut manifest = serde_json::Map::new();
+        for platform in ["linux-i386", "mac-os", "mac-os-arm64", "windows-arm64", "windows-x86"] {
+            manifest.insert(platform.to_string(), empty.clone());
+        }
+        manifest.insert("linux".to_string(), components);
+        manifest.insert("windows-x64".to_string(), serde_json::json!({ "java-runtime-gamma": [], "minecraft-java-exe": runtime("16.0.1.9.1") }));
+        manifest.insert("linux-riscv64".to_string(), serde_json::json!({ "java-runtime-delta": runtime("21.0.3") }));
+        serde_json::from_value(serde_json::Value::Object(manifest)).unwrap()
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let manifest = test_manifest();
+        let linux = manifest.runtimes(Platform::Linux).unwrap();
+        assert_eq!(linux.gamma()[0].to_string(), "17.0.8");
+        assert!(linux.delta().is_empty());
+        assert_eq!(linux.component("java-runtime-zeta").unwrap()[0].to_string(), "25.0.1");
+        assert!(linux.minecraft_java_exe.is_empty());
+
+        let windows = manifest.runtimes(Platform::WindowsX64).unwrap();
+        assert_eq!(windows.minecraft_java_exe[0].to_string(), "16.0.1.9.1");
+        assert!(windows.legacy().is_empty());
+        assert_eq!(manifest.platforms["linux-riscv64"].component("java-runtime-delta").unwrap()[0].to_string(), "21.0.3");
+
+        let serialized = serde_json::to_value(&manifest).unwrap();
+        assert_eq!(serialized["linux"]["java-runtime-zeta"][0]["version"]["name"], "25.0.1");
+        assert_eq!(serialized["windows-x64"]["minecraft-java-exe"][0]["version"]["name"], "16.0.1.9.1");
+    }
+
+    #[test]
+    fn platform_from_target() {
+        assert_eq!(Platform::from_target("linux", "x86_64"), Some(Platform::Linux));
+        assert_eq!(Platform::from_target("macos", "aarch64"), Some(Platform::MacOsArm64));
+        assert_eq!(Platform::from_target("windows", "x86"), Some(Platform::WindowsX86));
+        assert_eq!(Platform::from_target("linux", "aarch64"), None);
+        assert_eq!(Platform::from_target("freebsd", "x86_64"), None);
+    }
+
+    #[test]
+    fn runtime_for() {
+        let manifest = test_manifest();
+        let gamma = JavaVersion { component: "java-runtime-gamma".to_string(), major_version: 17 };
+        assert_eq!(manifest.runtime_for(&gamma, Platform::Linux).unwrap().to_string(), "17.0.8");
+        assert!(manifest.runtime_for(&gamma, Platform::WindowsX64).is_none());
+        assert_eq!(manifest.runtime_for(&JavaVersion::default(), Platform::Linux).unwrap().to_string(), "8u51");
+        let unknown = JavaVersion { component: "java-runtime-unknown".to_string(), major_version: 99 };
+        assert!(manifest.runtime_for(&unknown, Platform::Linux).is_none());
+        assert_eq!(Platform::MacOsArm64.to_string(), "mac-os-arm64");
+    }
+
+    #[tokio::test]
+    async fn installed_runtimes() {
+        let runtimes = Path::new("target/test/java-installed/runtime");
+        _ = std::fs::remove_dir_all(runtimes);
+        let files: Vec<JavaInstallationFile> = vec![serde_json::from_str(
+            r#"{ "type": "file", "downloads": { "raw": { "sha1": "23524be9dba14bc2f1975b37f95c3381771595c8", "size": 4, "url": "" } } }"#,
+        )
+        .unwrap()];
+        let files: Vec<_> = files.into_iter().map(|file| JavaInstallationFile { name: "bin/java".to_string(), ..file }).collect();
+        for (component, platform, version) in [("java-runtime-gamma", "linux", "17.0.1"), ("jre-legacy", "linux", "8u51"), ("jre-legacy", "mac-os", "8u51")] {
+            let directory = runtimes.join(component).join(platform).join(component);
+            std::fs::create_dir_all(&directory).unwrap();
+            write_metadata(&directory, version, &files).await.unwrap();
+        }
+        std::fs::create_dir_all(runtimes.join("jre-legacy/unknown-platform")).unwrap();
+
+        let sha1 = std::fs::read_to_string(runtimes.join("java-runtime-gamma/linux/java-runtime-gamma.sha1")).unwrap();
+        assert!(sha1.starts_with("bin/java /#// 23524be9dba14bc2f1975b37f95c3381771595c8 "));
+
+        let installed = InstalledRuntime::list(runtimes).await.unwrap();
+        let summary: Vec<_> = installed.iter().map(|runtime| (runtime.component.as_str(), runtime.platform, runtime.version.as_str())).collect();
+        assert_eq!(
+            summary,
+            [("java-runtime-gamma", Platform::Linux, "17.0.1"), ("jre-legacy", Platform::Linux, "8u51"), ("jre-legacy", Platform::MacOs, "8u51")]
+        );
+        assert_eq!(installed[0].directory, runtimes.join("java-runtime-gamma/linux/java-runtime-gamma"));
+
+        let manifest = test_manifest();
+        assert_eq!(installed[0].update(&manifest).unwrap().to_string(), "17.0.8");
+        assert!(installed[1].update(&manifest).is_none());
+        assert!(installed[2].update(&manifest).is_none());
+
+        installed[0].clone().uninstall().await.unwrap();
+        assert!(!runtimes.join("java-runtime-gamma").exists());
+        installed[2].clone().uninstall().await.unwrap();
+        assert!(runtimes.join("jre-legacy/linux/.version").exists());
+        assert_eq!(InstalledRuntime::list(runtimes).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn finalize_files_from_manifest() {
+        let directory = Path::new("target/test/java-finalize");
+        _ = std::fs::remove_dir_all(directory);
+        std::fs::create_dir_all(directory.join("bin")).unwrap();
+        std::fs::write(directory.join("bin/java"), b"").unwrap();
+
+        let files: HashMap<String, JavaInstallationFile> = serde_json::from_str(
+            r#"{
+                "bin": { "type": "directory" },
+                "bin/java": { "type": "file", "executable": true, "downloads": { "raw": { "sha1": "da39a3ee5e6b4b0d3255bfef95601890afd80709", "size": 0, "url": "" } } },
+                "legal/java.base": { "type": "directory" },
+                "bin/jre": { "type": "link", "target": "java" }
+            }"#,
+        )
+        .unwrap();
+        let files: Vec<_> = files
+            .into_iter()
+            .map(|(name, mut file)| {
+                file.name = name;
+                file
+            })
+            .collect();
+
+        finalize_files(directory, &files).await.unwrap();
+        // Running it again over an existing installation must succeed as well.
+        finalize_files(directory, &files).await.unwrap();
+
+        assert!(directory.join("legal/java.base").is_dir());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(directory.join("bin/java")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0o111);
+            assert_eq!(std::fs::read_link(directory.join("bin/jre")).unwrap(), Path::new("java"));
+        }
+    }
+
+    #[tokio::test]
+    async fn verify_and_repair() {
+        let base_url = serve_test_responses(vec![("/java", "java"), ("/release", "JAVA_VERSION=\"17\"")]).await;
+        let files: HashMap<String, JavaInstallationFile> = serde_json::from_str(
+            &r#"{
+                "bin": { "type": "directory" },
+                "bin/java": { "type": "file", "executable": true, "downloads": { "raw": { "sha1": "23524be9dba14bc2f1975b37f95c3381771595c8", "size": 4, "url": "{base}/java" } } },
+                "release": { "type": "file", "downloads": { "raw": { "sha1": "c6027a93f053af4df79ccdd42e1e8210dceac602", "size": 17, "url": "{base}/release" } } },
+                "bin/jre": { "type": "link", "target": "java" }
+            }"#
+            .replace("{base}", &base_url),
+        )
+        .unwrap();
+        let files: Vec<_> = files
+            .into_iter()
+            .map(|(name, mut file)| {
+                file.name = name;
+                file
+            })
+            .collect();
+
+        let directory = Path::new("target/test/java-repair");
+        _ = std::fs::remove_dir_all(directory);
+        std::fs::create_dir_all(directory.join("bin")).unwrap();
+        std::fs::write(directory.join("bin/java"), b"java").unwrap();
+        std::fs::write(directory.join("release"), b"JAVA_VERSION=\"11\"").unwrap();
+        std::fs::write(directory.join("bin/leftover"), b"").unwrap();
+
+        let result = verify_files("17.0.8".to_string(), directory, &files, 4).await.unwrap();
+        assert!(!result.is_valid());
+        assert_eq!(result.unexpected, ["bin/leftover"]);
+        let reason = |name: &str| result.failed.iter().find(|failure| failure.name == name).map(|failure| failure.reason.clone());
+        assert_eq!(reason("release"), Some(JavaValidationFailureReason::HashNotMatching));
+        assert_eq!(reason("bin"), None);
+        #[cfg(unix)]
+        {
+            assert_eq!(reason("bin/java"), Some(JavaValidationFailureReason::NotExecutable));
+            assert_eq!(reason("bin/jre"), Some(JavaValidationFailureReason::FileNotFound));
+        }
+
+        repair_files(directory, &files, &result, 4, None).await.unwrap();
+        let result = verify_files("17.0.8".to_string(), directory, &files, 4).await.unwrap();
+        assert!(result.is_valid(), "{:?}", result);
+        assert_eq!(std::fs::read_to_string(directory.join("release")).unwrap(), "JAVA_VERSION=\"17\"");
+    }
+
     #[tokio::test]
     async fn get_installation_files() {
         #[cfg(feature = "log")]
         setup_logging();
         let manifest = JavaManifest::fetch().await.unwrap();
-        let runtimes = [
-            &manifest.linux.alpha,
-            &manifest.linux.beta,
-            &manifest.linux.gamma,
-            &manifest.linux.delta,
-            &manifest.linux.gamma_snapshot,
-            &manifest.linux.epsilon,
-            &manifest.linux.legacy,
-            &manifest.linux_i386.alpha,
-            &manifest.linux_i386.beta,
-            &manifest.linux_i386.gamma,
-            &manifest.linux_i386.delta,
-            &manifest.linux_i386.gamma_snapshot,
-            &manifest.linux_i386.epsilon,
-            &manifest.linux_i386.legacy,
-            &manifest.macos.alpha,
-            &manifest.macos.beta,
-            &manifest.macos.gamma,
-            &manifest.macos.delta,
-            &manifest.macos.gamma_snapshot,
-            &manifest.macos.epsilon,
-            &manifest.macos.legacy,
-            &manifest.macos_arm64.alpha,
-            &manifest.macos_arm64.beta,
-            &manifest.macos_arm64.gamma,
-            &manifest.macos_arm64.delta,
-            &manifest.macos_arm64.gamma_snapshot,
-            &manifest.macos_arm64.epsilon,
-            &manifest.macos_arm64.legacy,
-            &manifest.windows_arm64.alpha,
-            &manifest.windows_arm64.beta,
-            &manifest.windows_arm64.gamma,
-            &manifest.windows_arm64.delta,
-            &manifest.windows_arm64.gamma_snapshot,
-            &manifest.windows_arm64.epsilon,
-            &manifest.windows_arm64.legacy,
-            &manifest.windows_x64.alpha,
-            &manifest.windows_x64.beta,
-            &manifest.windows_x64.gamma,
-            &manifest.windows_x64.delta,
-            &manifest.windows_x64.gamma_snapshot,
-            &manifest.windows_x64.epsilon,
-            &manifest.windows_x64.legacy,
-            &manifest.windows_x86.alpha,
-            &manifest.windows_x86.beta,
-            &manifest.windows_x86.gamma,
-            &manifest.windows_x86.delta,
-            &manifest.windows_x86.gamma_snapshot,
-            &manifest.windows_x86.epsilon,
-            &manifest.windows_x86.legacy,
-        ];
+        let runtimes: Vec<&Vec<JavaRuntime>> = manifest.platforms.values().flat_map(|runtimes| runtimes.components.values()).collect();
 
         let results: Vec<_> = stream::iter(runtimes)
             .enumerate()
@@ -297,57 +992,7 @@ mod test {
         setup_logging();
         let manifest = JavaManifest::fetch().await.unwrap();
         let directory = "target/test/";
-        let runtimes = [
-            &manifest.linux.alpha,
-            &manifest.linux.beta,
-            &manifest.linux.gamma,
-            &manifest.linux.delta,
-            &manifest.linux.gamma_snapshot,
-            &manifest.linux.epsilon,
-            &manifest.linux.legacy,
-            &manifest.linux_i386.alpha,
-            &manifest.linux_i386.beta,
-            &manifest.linux_i386.gamma,
-            &manifest.linux_i386.delta,
-            &manifest.linux_i386.gamma_snapshot,
-            &manifest.linux_i386.epsilon,
-            &manifest.linux_i386.legacy,
-            &manifest.macos.alpha,
-            &manifest.macos.beta,
-            &manifest.macos.gamma,
-            &manifest.macos.delta,
-            &manifest.macos.gamma_snapshot,
-            &manifest.macos.epsilon,
-            &manifest.macos.legacy,
-            &manifest.macos_arm64.alpha,
-            &manifest.macos_arm64.beta,
-            &manifest.macos_arm64.gamma,
-            &manifest.macos_arm64.delta,
-            &manifest.macos_arm64.gamma_snapshot,
-            &manifest.macos_arm64.epsilon,
-            &manifest.macos_arm64.legacy,
-            &manifest.windows_arm64.alpha,
-            &manifest.windows_arm64.beta,
-            &manifest.windows_arm64.gamma,
-            &manifest.windows_arm64.delta,
-            &manifest.windows_arm64.gamma_snapshot,
-            &manifest.windows_arm64.epsilon,
-            &manifest.windows_arm64.legacy,
-            &manifest.windows_x64.alpha,
-            &manifest.windows_x64.beta,
-            &manifest.windows_x64.gamma,
-            &manifest.windows_x64.delta,
-            &manifest.windows_x64.gamma_snapshot,
-            &manifest.windows_x64.epsilon,
-            &manifest.windows_x64.legacy,
-            &manifest.windows_x86.alpha,
-            &manifest.windows_x86.beta,
-            &manifest.windows_x86.gamma,
-            &manifest.windows_x86.delta,
-            &manifest.windows_x86.gamma_snapshot,
-            &manifest.windows_x86.epsilon,
-            &manifest.windows_x86.legacy,
-        ];
+        let runtimes: Vec<&Vec<JavaRuntime>> = manifest.platforms.values().flat_map(|runtimes| runtimes.components.values()).collect();
 
         let results: Vec<_> = stream::iter(runtimes)
             .map(|runtime| async move {
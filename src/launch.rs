@@ -0,0 +1,418 @@
+//! Assembly of the complete `java` invocation that starts a version.
+//!
+//! [`LaunchCommand::builder`] starts from a [`VersionManifest`] and the game directory, and
+//! defaults every other path to the layout used by the official launcher. The result can be
+//! inspected before it is turned into a [`std::process::Command`].
+//!
+//! # Example
+//! ```no_run
+//! use piston_mc::arguments::ArgumentContext;
+//! use piston_mc::launch::LaunchCommand;
+//! use piston_mc::manifest_v2::ManifestV2;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let manifest = ManifestV2::fetch().await.unwrap();
+//!     let version = manifest.version("1.20.1").await.unwrap().unwrap();
+//!     let command = LaunchCommand::builder(&version, ".minecraft")
+//!         .java("runtime/java-runtime-gamma/bin/java")
+//!         .context(ArgumentContext { auth_player_name: "Steve".to_string(), ..Default::default() })
+//!         .jvm_argument("-Xmx2G")
+//!         .build();
+//!     println!("{}", command);
+//!     command.to_command().spawn().unwrap();
+//! }
+//! ```
+
+use crate::arguments::{ArgumentContext, CLASSPATH_SEPARATOR};
+#[cfg(feature = "assets")]
+use crate::assets::Assets;
+use crate::game_directory::GameDirectory;
+use crate::manifest_v2::ReleaseType;
+use crate::rules::RuleEnvironment;
+use crate::version_manifest::VersionManifest;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A fully resolved launch of a version.
+#[derive(Clone, Debug)]
+pub struct LaunchCommand {
+    pub java: PathBuf,
+    pub jvm_arguments: Vec<String>,
+    pub main_class: String,
+    pub game_arguments: Vec<String>,
+    pub working_directory: PathBuf,
+}
+
+/// Builder for a [`LaunchCommand`], created through [`LaunchCommand::builder`].
+#[derive(Clone, Debug)]
+pub struct LaunchCommandBuilder<'a> {
+    manifest: &'a VersionManifest,
+    game_directory: PathBuf,
+    java: PathBuf,
+    libraries_directory: Option<PathBuf>,
+    assets_directory: Option<PathBuf>,
+    game_assets_directory: Option<PathBuf>,
+    virtual_assets: Option<String>,
+    map_to_resources: bool,
+    natives_directory: Option<PathBuf>,
+    client_jar: Option<PathBuf>,
+    logging_argument: Option<String>,
+    extra_jvm_arguments: Vec<String>,
+    context: ArgumentContext,
+    environment: RuleEnvironment,
+}
+
+impl LaunchCommand {
+    pub fn builder(manifest: &VersionManifest, game_directory: impl AsRef<Path>) -> LaunchCommandBuilder<'_> {
+        LaunchCommandBuilder {
+            manifest,
+            game_directory: game_directory.as_ref().to_path_buf(),
+            java: PathBuf::from("java"),
+            libraries_directory: None,
+            assets_directory: None,
+            game_assets_directory: None,
+            virtual_assets: None,
+            map_to_resources: false,
+            natives_directory: None,
+            client_jar: None,
+            logging_argument: None,
+            extra_jvm_arguments: vec![],
+            context: ArgumentContext::default(),
+            environment: RuleEnvironment::current(),
+        }
+    }
+
+    /// Returns every argument passed to the java executable, in order.
+    pub fn arguments(&self) -> Vec<String> {
+        let mut arguments = self.jvm_arguments.clone();
+        arguments.push(self.main_class.clone());
+        arguments.extend(self.game_arguments.iter().cloned());
+        arguments
+    }
+
+    pub fn to_command(&self) -> Command {
+        let mut command = Command::new(&self.java);
+        command.args(self.arguments()).current_dir(&self.working_directory);
+        command
+    }
+}
+
+impl Display for LaunchCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", quote(&self.java.to_string_lossy()))?;
+        for argument in self.arguments() {
+            write!(f, " {}", quote(&argument))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> LaunchCommandBuilder<'a> {
+    /// Path to the java executable, defaults to `java` from the `PATH`.
+    pub fn java(mut self, java: impl AsRef<Path>) -> Self {
+        self.java = java.as_ref().to_path_buf();
+        self
+    }
+
+    /// Defaults to `<game directory>/libraries`.
+    pub fn libraries_directory(mut self, directory: impl AsRef<Path>) -> Self {
+        self.libraries_directory = Some(directory.as_ref().to_path_buf());
+        self
+    }
+
+    /// Defaults to `<game directory>/assets`.
+    pub fn assets_directory(mut self, directory: impl AsRef<Path>) -> Self {
+        self.assets_directory = Some(directory.as_ref().to_path_buf());
+        self
+    }
+
+    /// The `${game_assets}` directory legacy versions read their assets from by name, see `Assets::game_assets`.
+    ///
+    /// Defaults to the assets directory, or the directory the index set through [`LaunchCommandBuilder::asset_index`]
+    /// is reconstructed in.
+    pub fn game_assets_directory(mut self, directory: impl AsRef<Path>) -> Self {
+        self.game_assets_directory = Some(directory.as_ref().to_path_buf());
+        self
+    }
+
+    /// Takes the `${game_assets}` directory from the `virtual` and `map_to_resources` flags of the
+    /// asset index of the version: `<assets directory>/virtual/<asset index>` or `<game directory>/resources`.
+    #[cfg(feature = "assets")]
+    pub fn asset_index(mut self, assets: &Assets) -> Self {
+        self.virtual_assets = assets.is_virtual.then(|| assets.asset_id.clone());
+        self.map_to_resources = assets.map_to_resources;
+        self
+    }
+
+    /// Defaults to `<game directory>/versions/<id>/natives`.
+    pub fn natives_directory(mut self, directory: impl AsRef<Path>) -> Self {
+        self.natives_directory = Some(directory.as_ref().to_path_buf());
+        self
+    }
+
+    /// Defaults to `<game directory>/versions/<id>/<id>.jar`.
+    pub fn client_jar(mut self, path: impl AsRef<Path>) -> Self {
+        self.client_jar = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Takes the libraries, assets and natives directories and the client jar from `layout`,
+    /// for instances whose game directory is separate from the shared installation.
+    pub fn layout(mut self, layout: &GameDirectory) -> Self {
+        self.libraries_directory = Some(layout.libraries());
+        self.assets_directory = Some(layout.assets());
+        self.natives_directory = Some(layout.natives(&self.manifest.id));
+        self.client_jar = Some(layout.client_jar(&self.manifest.id));
+        self
+    }
+
+    /// The already substituted logging argument, e.g. `-Dlog4j.configurationFile=<path>`.
+    ///
+    /// Defaults to the logging argument of the manifest pointing at `<assets directory>/log_configs/<file>`,
+    /// where [`VersionManifest::download_log_config`] puts the configuration.
+    pub fn logging_argument(mut self, argument: impl Into<String>) -> Self {
+        self.logging_argument = Some(argument.into());
+        self
+    }
+
+    /// Adds a JVM argument such as `-Xmx2G` in front of the arguments from the manifest.
+    pub fn jvm_argument(mut self, argument: impl Into<String>) -> Self {
+        self.extra_jvm_arguments.push(argument.into());
+        self
+    }
+
+    /// Account and window details used for the argument placeholders.
+    ///
+    /// Directories and the classpath are always filled in by the builder; the version name and
+    /// type are taken from the manifest unless they are set.
+    pub fn context(mut self, context: ArgumentContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// The environment rules are evaluated against, defaults to [`RuleEnvironment::current`].
+    pub fn environment(mut self, environment: RuleEnvironment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Returns the classpath entries: every allowed library artifact followed by the client jar.
+    ///
+    /// Libraries with `natives` are left out, their jars are extracted by [`VersionManifest::extract_natives`] instead.
+    pub fn classpath(&self) -> Vec<PathBuf> {
+        let libraries_directory = self.resolved_libraries_directory();
+        let mut classpath: Vec<PathBuf> = self
+            .manifest
+            .libraries
+            .iter()
+            .filter(|library| library.is_allowed(&self.environment) && library.natives.is_none())
+            .filter_map(|library| library.artifact())
+            .map(|artifact| libraries_directory.join(artifact.path))
+            .collect();
+        classpath.push(self.resolved_client_jar());
+        classpath
+    }
+
+    pub fn build(self) -> LaunchCommand {
+        let manifest = self.manifest;
+        let classpath = self.classpath();
+
+        let mut context = self.context.clone();
+        if context.version_name.is_empty() {
+            context.version_name = manifest.id.clone();
+        }
+        if context.version_type.is_empty() {
+            context.version_type = version_type(&manifest.release_type).to_string();
+        }
+        context.game_directory = self.game_directory.clone();
+        context.assets_root = self.resolved_assets_directory();
+        context.game_assets = self.resolved_game_assets_directory();
+        context.assets_index_name = manifest.assets.clone().unwrap_or_default();
+        context.natives_directory = self.resolved_natives_directory();
+        context.library_directory = self.resolved_libraries_directory();
+        context.classpath = classpath.iter().map(|path| path.to_string_lossy()).collect::<Vec<_>>().join(CLASSPATH_SEPARATOR);
+        context.classpath_separator = CLASSPATH_SEPARATOR.to_string();
+
+        let mut jvm_arguments = self.extra_jvm_arguments.clone();
+        jvm_arguments.extend(manifest.jvm_arguments(&context, &self.environment));
+        if let Some(logging_argument) = self.resolved_logging_argument() {
+            jvm_arguments.push(logging_argument);
+        }
+
+        LaunchCommand {
+            java: self.java.clone(),
+            jvm_arguments,
+            main_class: manifest.main_class.clone(),
+            game_arguments: manifest.game_arguments(&context, &self.environment),
+            working_directory: self.game_directory.clone(),
+        }
+    }
+
+    fn default_layout(&self) -> GameDirectory {
+        GameDirectory::new(&self.game_directory)
+    }
+
+    fn resolved_libraries_directory(&self) -> PathBuf {
+        self.libraries_directory.clone().unwrap_or_else(|| self.default_layout().libraries())
+    }
+
+    fn resolved_assets_directory(&self) -> PathBuf {
+        self.assets_directory.clone().unwrap_or_else(|| self.default_layout().assets())
+    }
+
+    fn resolved_game_assets_directory(&self) -> PathBuf {
+        if let Some(directory) = &self.game_assets_directory {
+            return directory.clone();
+        }
+        if self.map_to_resources {
+            return self.default_layout().resources();
+        }
+        match &self.virtual_assets {
+            Some(asset_id) => self.resolved_assets_directory().join("virtual").join(asset_id),
+            None => self.resolved_assets_directory(),
+        }
+    }
+
+    fn resolved_logging_argument(&self) -> Option<String> {
+        if let Some(argument) = &self.logging_argument {
+            return Some(argument.clone());
+        }
+        let logging = self.manifest.logging.as_ref()?;
+        let name = logging.client.file.id.as_ref()?;
+        Some(logging.client.jvm_argument(self.resolved_assets_directory().join("log_configs").join(name)))
+    }
+
+    fn resolved_natives_directory(&self) -> PathBuf {
+        self.natives_directory.clone().unwrap_or_else(|| self.default_layout().natives(&self.manifest.id))
+    }
+
+    fn resolved_client_jar(&self) -> PathBuf {
+        self.client_jar.clone().unwrap_or_else(|| self.default_layout().client_jar(&self.manifest.id))
+    }
+}
+
+/// The `${version_type}` value the official launcher passes for each release type.
+fn version_type(release_type: &ReleaseType) -> &'static str {
+    match release_type {
+        ReleaseType::Release => "release",
+        ReleaseType::Snapshot => "snapshot",
+        ReleaseType::OldBeta => "old_beta",
+        ReleaseType::OldAlpha => "old_alpha",
+    }
+}
+
+fn quote(argument: &str) -> String {
+    if argument.is_empty() || argument.contains(char::is_whitespace) {
+        format!("\"{}\"", argument.replace('"', "\\\""))
+    } else {
+        argument.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arguments::{ArgumentContext, CLASSPATH_SEPARATOR};
+    use crate::launch::LaunchCommand;
+    use crate::rules::RuleEnvironment;
+    use crate::version_manifest::VersionManifest;
+    use std::path::Path;
+
+    const MANIFEST: &str = r#"{
+        "id": "1.20.1",
+        "mainClass": "net.minecraft.client.main.Main",
+        "minimumLauncherVersion": 21,
+        "releaseTime": "2023-06-12T13:25:51+00:00",
+        "time": "2023-06-12T13:25:51+00:00",
+        "type": "release",
+        "arguments": {
+            "game": ["--username", "${auth_player_name}", "--version", "${version_name}", "--gameDir", "${game_directory}", "--assetIndex", "${assets_index_name}", "--versionType", "${version_type}"],
+            "jvm": [
+                { "rules": [{ "action": "allow", "os": { "name": "osx" } }], "value": ["-XstartOnFirstThread"] },
+                "-Djava.library.path=${natives_directory}",
+                "-cp",
+                "${classpath}"
+            ]
+        },
+        "assetIndex": { "id": "5", "sha1": "", "size": 0, "totalSize": 0, "url": "" },
+        "assets": "5",
+        "downloads": { "client": { "sha1": "", "size": 0, "url": "" } },
+        "libraries": [
+            {
+                "name": "com.mojang:logging:1.1.1",
+                "downloads": { "artifact": { "path": "com/mojang/logging/1.1.1/logging-1.1.1.jar", "sha1": "", "size": 0, "url": "" } }
+            },
+            {
+                "name": "ca.weblite:java-objc-bridge:1.1",
+                "downloads": { "artifact": { "path": "ca/weblite/java-objc-bridge/1.1/java-objc-bridge-1.1.jar", "sha1": "", "size": 0, "url": "" } },
+                "rules": [{ "action": "allow", "os": { "name": "osx" } }]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn build() {
+        let manifest: VersionManifest = serde_json::from_str(MANIFEST).unwrap();
+        let command = LaunchCommand::builder(&manifest, "game")
+            .java("bin/java")
+            .environment(RuleEnvironment::new("linux", "x86_64"))
+            .context(ArgumentContext { auth_player_name: "Steve".to_string(), ..Default::default() })
+            .jvm_argument("-Xmx2G")
+            .logging_argument("-Dlog4j.configurationFile=client-1.12.xml")
+            .build();
+
+        let libraries = Path::new("game").join("libraries");
+        let version = Path::new("game").join("versions").join("1.20.1");
+        let classpath = [libraries.join("com/mojang/logging/1.1.1/logging-1.1.1.jar"), version.join("1.20.1.jar")]
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join(CLASSPATH_SEPARATOR);
+
+        assert_eq!(command.java, Path::new("bin/java"));
+        assert_eq!(
+            command.jvm_arguments,
+            vec![
+                "-Xmx2G".to_string(),
+                format!("-Djava.library.path={}", version.join("natives").display()),
+                "-cp".to_string(),
+                classpath,
+                "-Dlog4j.configurationFile=client-1.12.xml".to_string(),
+            ]
+        );
+        assert_eq!(command.main_class, "net.minecraft.client.main.Main");
+        assert_eq!(
+            command.game_arguments,
+            vec!["--username", "Steve", "--version", "1.20.1", "--gameDir", "game", "--assetIndex", "5", "--versionType", "release"]
+        );
+        assert_eq!(command.to_command().get_args().count(), 5 + 1 + 10);
+    }
+
+    #[test]
+    #[cfg(feature = "assets")]
+    fn legacy_asset_index() {
+        let manifest: VersionManifest = serde_json::from_str(MANIFEST).unwrap();
+        let mut assets: crate::assets::Assets =
+            serde_json::from_str(r#"{ "url": "", "asset_id": "legacy", "virtual": true, "objects": {} }"#).unwrap();
+        let command = LaunchCommand::builder(&manifest, "game").asset_index(&assets);
+        assert_eq!(command.resolved_game_assets_directory(), Path::new("game/assets/virtual/legacy"));
+
+        assets.map_to_resources = true;
+        let command = LaunchCommand::builder(&manifest, "game").asset_index(&assets);
+        assert_eq!(command.resolved_game_assets_directory(), Path::new("game/resources"));
+        assert_eq!(LaunchCommand::builder(&manifest, "game").resolved_game_assets_directory(), Path::new("game/assets"));
+    }
+
+    #[test]
+    fn logging_from_manifest() {
+        let mut manifest: VersionManifest = serde_json::from_str(MANIFEST).unwrap();
+        manifest.logging = serde_json::from_str(
+            r#"{ "client": { "argument": "-Dlog4j.configurationFile=${path}", "type": "log4j2-xml", "file": { "id": "client-1.12.xml", "sha1": "", "size": 0, "url": "" } } }"#,
+        )
+        .unwrap();
+        let command = LaunchCommand::builder(&manifest, "game").environment(RuleEnvironment::new("linux", "x86_64")).build();
+        let path = Path::new("game").join("assets").join("log_configs").join("client-1.12.xml");
+        assert_eq!(command.jvm_arguments.last().unwrap(), &format!("-Dlog4j.configurationFile={}", path.display()));
+    }
+}
@@ -9,13 +9,30 @@ extern crate log;
 #[macro_use]
 mod log_stub;
 
+pub mod arguments;
 #[cfg(feature = "assets")]
 pub mod assets;
 //#[cfg(feature = "downloads")]
 //pub mod download_util;
+#[cfg(feature = "fabric")]
+pub mod fabric;
+#[cfg(feature = "forge")]
+pub mod forge;
+pub mod game_directory;
+#[cfg(feature = "downloads")]
+mod http;
+#[cfg(feature = "installer")]
+pub mod installer;
 #[cfg(feature = "java")]
 pub mod java;
+pub mod launch;
+#[cfg(feature = "lzma")]
+mod lzma;
 pub mod manifest_v2;
+pub mod maven;
+pub mod natives;
+#[cfg(feature = "quilt")]
+pub mod quilt;
 #[cfg(feature = "news")]
 pub mod news;
 #[cfg(any(
@@ -26,7 +43,9 @@ pub mod news;
     feature = "launcher-patch-notes"
 ))]
 pub mod patch_notes;
+pub mod rules;
 pub mod sha_validation;
+pub mod system_java;
 pub mod version_manifest;
 
 #[cfg(test)]
@@ -34,3 +53,37 @@ pub mod version_manifest;
 pub(crate) fn setup_logging() {
     _ = pretty_env_logger::env_logger::builder().is_test(true).format_timestamp(None).filter_level(log::LevelFilter::Trace).try_init();
 }
+
+/// Serves canned `(path, body)` responses over plain HTTP on a random local port, standing in
+/// for the meta APIs in tests. Returns the base url of the server.
+#[cfg(test)]
+#[cfg(any(feature = "fabric", feature = "quilt", feature = "java", feature = "assets"))]
+pub(crate) async fn serve_test_responses<B: Into<Vec<u8>>>(responses: Vec<(&'static str, B)>) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let responses: Vec<(&'static str, Vec<u8>)> = responses.into_iter().map(|(route, body)| (route, body.into())).collect();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut buffer = vec![0u8; 8192];
+            let read = stream.read(&mut buffer).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buffer[..read]);
+            let path = request.split_whitespace().nth(1).unwrap_or_default();
+            let response = match responses.iter().find(|(route, _)| *route == path) {
+                Some((_, body)) => {
+                    let mut response =
+                        format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len())
+                            .into_bytes();
+                    response.extend_from_slice(body);
+                    response
+                }
+                None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+            };
+            _ = stream.write_all(&response).await;
+        }
+    });
+    format!("http://{}", address)
+}
+
@@ -0,0 +1,229 @@
+//! Downloads of java runtime files through their lzma compressed variant.
+//!
+//! The compressed payload is decompressed while it streams in, and the decompressed file is
+//! validated against the sha1 of the raw file, so the result is identical to downloading `raw`.
+
+use crate::java::Downloads;
+use crate::sha_validation::{SHAError, validate_file};
+use anyhow::Result;
+use futures_util::stream::{self, StreamExt};
+use sha1::{Digest, Sha1};
+use simple_download_utility::MultiDownloadProgress;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+
+/// How often progress is reported at most while files are downloading.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Shared progress of a batch of downloads, reported through the optional sender.
+struct Progress {
+    sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    start_time: Instant,
+    bytes_to_download: usize,
+    file_names: Vec<String>,
+    state: Mutex<ProgressState>,
+}
+
+struct ProgressState {
+    bytes_downloaded: usize,
+    file_names_downloaded: Vec<String>,
+    last_sent: Option<Instant>,
+}
+
+/// Downloads every `(path, downloads)` pair, using the lzma variant when there is one.
+///
+/// Files that are already present with the sha1 of the raw file are skipped.
+pub(crate) async fn download_files(
+    files: Vec<(PathBuf, Downloads)>,
+    parallel: u16,
+    sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+) -> Result<()> {
+    let files = tokio::task::spawn_blocking(move || {
+        files.into_iter().filter(|(path, downloads)| !validate_file(path, &downloads.raw.sha1)).collect::<Vec<_>>()
+    })
+    .await?;
+    let progress = Progress {
+        sender,
+        start_time: Instant::now(),
+        bytes_to_download: files.iter().map(|(_, downloads)| downloads.lzma.as_ref().unwrap_or(&downloads.raw).size).sum(),
+        file_names: files.iter().map(|(path, _)| file_name(path)).collect(),
+        state: Mutex::new(ProgressState { bytes_downloaded: 0, file_names_downloaded: vec![], last_sent: None }),
+    };
+    let client = reqwest::Client::new();
+
+    let results: Vec<Result<()>> = stream::iter(files)
+        .map(|(path, downloads)| {
+            let (client, progress) = (&client, &progress);
+            async move {
+                download_file(client, &path, &downloads, progress).await?;
+                progress.file_downloaded(file_name(&path)).await;
+                Ok(())
+            }
+        })
+        .buffer_unordered(parallel as usize)
+        .collect()
+        .await;
+    progress.finish().await;
+
+    for result in results {
+        result?;
+    }
+    Ok(())
+}
+
+async fn download_file(client: &reqwest::Client, path: &Path, downloads: &Downloads, progress: &Progress) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let url = &downloads.lzma.as_ref().unwrap_or(&downloads.raw).url;
+    let mut response = client.get(url).send().await?.error_for_status()?.bytes_stream();
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut hasher = Sha1::new();
+
+    match &downloads.lzma {
+        Some(_) => {
+            let mut decompressor = lzma_rs::decompress::Stream::new(Vec::new());
+            while let Some(chunk) = response.next().await {
+                let chunk = chunk?;
+                decompressor.write_all(&chunk)?;
+                if let Some(output) = decompressor.get_output_mut() {
+                    hasher.update(&output[..]);
+                    file.write_all(output).await?;
+                    output.clear();
+                }
+                progress.bytes_downloaded(chunk.len()).await;
+            }
+            let output = decompressor.finish()?;
+            hasher.update(&output);
+            file.write_all(&output).await?;
+        }
+        None => {
+            while let Some(chunk) = response.next().await {
+                let chunk = chunk?;
+                hasher.update(&chunk);
+                file.write_all(&chunk).await?;
+                progress.bytes_downloaded(chunk.len()).await;
+            }
+        }
+    }
+    file.flush().await?;
+
+    let computed: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+    if !computed.eq_ignore_ascii_case(&downloads.raw.sha1) {
+        drop(file);
+        _ = tokio::fs::remove_file(path).await;
+        return Err(SHAError::FailedValidation(path.to_string_lossy().into_owned()).into());
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+impl Progress {
+    async fn bytes_downloaded(&self, bytes: usize) {
+        let progress = {
+            let mut state = self.state.lock().unwrap();
+            state.bytes_downloaded += bytes;
+            self.snapshot(&mut state, false)
+        };
+        self.send(progress).await;
+    }
+
+    async fn file_downloaded(&self, name: String) {
+        let progress = {
+            let mut state = self.state.lock().unwrap();
+            state.file_names_downloaded.push(name);
+            self.snapshot(&mut state, false)
+        };
+        self.send(progress).await;
+    }
+
+    /// Reports the final state, which throttling may have held back.
+    async fn finish(&self) {
+        let progress = self.snapshot(&mut self.state.lock().unwrap(), true);
+        self.send(progress).await;
+    }
+
+    /// Builds the progress to report, `None` if there is no sender or the last report was too recent.
+    fn snapshot(&self, state: &mut ProgressState, force: bool) -> Option<MultiDownloadProgress> {
+        self.sender.as_ref()?;
+        if !force && state.last_sent.is_some_and(|last_sent| last_sent.elapsed() < PROGRESS_INTERVAL) {
+            return None;
+        }
+        state.last_sent = Some(Instant::now());
+        let elapsed = self.start_time.elapsed().as_secs_f64();
+        Some(MultiDownloadProgress {
+            bytes_to_download: self.bytes_to_download,
+            bytes_downloaded: state.bytes_downloaded,
+            bytes_per_second: if elapsed > 0.0 { (state.bytes_downloaded as f64 / elapsed) as usize } else { 0 },
+            files_downloaded: state.file_names_downloaded.len(),
+            files_total: self.file_names.len(),
+            file_names_downloaded: state.file_names_downloaded.clone(),
+            file_names: self.file_names.clone(),
+        })
+    }
+
+    async fn send(&self, progress: Option<MultiDownloadProgress>) {
+        if let (Some(sender), Some(progress)) = (&self.sender, progress) {
+            _ = sender.send(progress).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::java::{DownloadItem, Downloads};
+    use crate::lzma::download_files;
+    use crate::serve_test_responses;
+    use sha1::{Digest, Sha1};
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn download_compressed() {
+        let content = "public class Main {}\n".repeat(100);
+        let mut compressed = Vec::new();
+        lzma_rs::lzma_compress(&mut content.as_bytes(), &mut compressed).unwrap();
+        let sha1: String = Sha1::digest(content.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect();
+        let base_url = serve_test_responses(vec![("/file.lzma", compressed.clone()), ("/file", content.clone().into_bytes())]).await;
+
+        let directory = Path::new("target/test/lzma");
+        _ = std::fs::remove_dir_all(directory);
+        let raw = DownloadItem { sha1: sha1.clone(), size: content.len(), url: format!("{}/file", base_url) };
+        let lzma = DownloadItem { sha1: "unused".to_string(), size: compressed.len(), url: format!("{}/file.lzma", base_url) };
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(64);
+        download_files(
+            vec![
+                (directory.join("lib/compressed"), Downloads { lzma: Some(lzma.clone()), raw: raw.clone() }),
+                (directory.join("lib/raw"), Downloads { lzma: None, raw: raw.clone() }),
+            ],
+            2,
+            Some(sender),
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read_to_string(directory.join("lib/compressed")).unwrap(), content);
+        assert_eq!(std::fs::read_to_string(directory.join("lib/raw")).unwrap(), content);
+
+        let mut last = None;
+        while let Some(progress) = receiver.recv().await {
+            last = Some(progress);
+        }
+        let last = last.unwrap();
+        assert_eq!(last.files_downloaded, 2);
+        assert_eq!(last.bytes_downloaded, compressed.len() + content.len());
+
+        // Both files are valid now, so nothing is requested even though the url does not exist.
+        let missing = DownloadItem { url: format!("{}/missing", base_url), ..raw.clone() };
+        download_files(vec![(directory.join("lib/compressed"), Downloads { lzma: None, raw: missing })], 1, None).await.unwrap();
+
+        let corrupted = DownloadItem { sha1: "0000".to_string(), ..raw };
+        let result = download_files(vec![(directory.join("lib/corrupted"), Downloads { lzma: Some(lzma), raw: corrupted })], 1, None).await;
+        assert!(result.is_err());
+        assert!(!directory.join("lib/corrupted").exists());
+    }
+}
@@ -0,0 +1,193 @@
+//! Maven coordinates as used in library names, e.g. `org.lwjgl:lwjgl:3.3.1:natives-linux@jar`.
+//!
+//! Vanilla libraries carry a `downloads` block with the exact path and url of their jar, but
+//! libraries added by mod loaders often only have a `name` and the base `url` of the maven
+//! repository hosting them. [`LibraryItem::artifact`] resolves both kinds to a [`LibraryArtifact`].
+//!
+//! # Example
+//! ```
+//! use piston_mc::maven::MavenCoordinate;
+//!
+//! let coordinate: MavenCoordinate = "org.lwjgl:lwjgl:3.3.1:natives-linux".parse().unwrap();
+//! assert_eq!(coordinate.path(), "org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.jar");
+//! assert_eq!(
+//!     coordinate.url("https://libraries.minecraft.net/"),
+//!     "https://libraries.minecraft.net/org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.jar"
+//! );
+//! ```
+
+use crate::version_manifest::LibraryItem;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The repository vanilla libraries are served from, used when a library does not name its own.
+pub const MINECRAFT_LIBRARIES_URL: &str = "https://libraries.minecraft.net/";
+
+#[derive(Debug, thiserror::Error)]
+pub enum MavenError {
+    #[error("Invalid maven coordinate: {0}")]
+    InvalidCoordinate(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MavenCoordinate {
+    pub group: String,
+    pub artifact: String,
+    pub version: String,
+    pub classifier: Option<String>,
+    /// File extension without the dot, `jar` unless the coordinate ends in `@<extension>`.
+    pub extension: String,
+}
+
+/// A library jar, resolved either from the `downloads` block or from the maven coordinate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LibraryArtifact {
+    /// Path relative to the libraries directory, in maven layout.
+    pub path: String,
+    pub url: String,
+    pub sha1: Option<String>,
+    pub size: Option<u64>,
+}
+
+impl MavenCoordinate {
+    /// Returns the path of this artifact relative to the root of a maven repository.
+    pub fn path(&self) -> String {
+        format!("{}/{}/{}/{}", self.group.replace('.', "/"), self.artifact, self.version, self.file_name())
+    }
+
+    /// Returns the url of this artifact in the repository at `repository`.
+    pub fn url(&self, repository: impl AsRef<str>) -> String {
+        format!("{}/{}", repository.as_ref().trim_end_matches('/'), self.path())
+    }
+
+    pub fn file_name(&self) -> String {
+        match &self.classifier {
+            Some(classifier) => format!("{}-{}-{}.{}", self.artifact, self.version, classifier, self.extension),
+            None => format!("{}-{}.{}", self.artifact, self.version, self.extension),
+        }
+    }
+}
+
+impl FromStr for MavenCoordinate {
+    type Err = MavenError;
+
+    fn from_str(coordinate: &str) -> Result<Self, Self::Err> {
+        let invalid = || MavenError::InvalidCoordinate(coordinate.to_string());
+        let (coordinate_part, extension) = match coordinate.split_once('@') {
+            Some((coordinate_part, extension)) => (coordinate_part, extension),
+            None => (coordinate, "jar"),
+        };
+
+        let parts: Vec<&str> = coordinate_part.split(':').collect();
+        if parts.len() < 3 || parts.len() > 4 || extension.is_empty() || parts.iter().any(|part| part.is_empty()) {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            group: parts[0].to_string(),
+            artifact: parts[1].to_string(),
+            version: parts[2].to_string(),
+            classifier: parts.get(3).map(|classifier| classifier.to_string()),
+            extension: extension.to_string(),
+        })
+    }
+}
+
+impl Display for MavenCoordinate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.group, self.artifact, self.version)?;
+        if let Some(classifier) = &self.classifier {
+            write!(f, ":{}", classifier)?;
+        }
+        if self.extension != "jar" {
+            write!(f, "@{}", self.extension)?;
+        }
+        Ok(())
+    }
+}
+
+impl LibraryItem {
+    pub fn coordinate(&self) -> Result<MavenCoordinate, MavenError> {
+        self.name.parse()
+    }
+
+    /// Returns the jar that goes on the classpath for this library, if it has one.
+    ///
+    /// Libraries without a `downloads` block are resolved from their coordinate against `url`,
+    /// or the Minecraft libraries repository if they do not name one.
+    pub fn artifact(&self) -> Option<LibraryArtifact> {
+        if let Some(downloads) = &self.downloads {
+            let artifact = downloads.artifact.as_ref()?;
+            let path = match &artifact.path {
+                Some(path) => path.clone(),
+                None => self.coordinate().ok()?.path(),
+            };
+            return Some(LibraryArtifact { path, url: artifact.url.clone(), sha1: Some(artifact.sha1.clone()), size: Some(artifact.size) });
+        }
+
+        if self.natives.is_some() {
+            return None;
+        }
+        let coordinate = self.coordinate().ok()?;
+        Some(LibraryArtifact {
+            path: coordinate.path(),
+            url: coordinate.url(self.url.as_deref().unwrap_or(MINECRAFT_LIBRARIES_URL)),
+            sha1: self.sha1.clone(),
+            size: self.size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maven::*;
+
+    #[test]
+    fn parse() {
+        let coordinate: MavenCoordinate = "org.lwjgl:lwjgl:3.3.1:natives-linux@zip".parse().unwrap();
+        assert_eq!(coordinate.group, "org.lwjgl");
+        assert_eq!(coordinate.artifact, "lwjgl");
+        assert_eq!(coordinate.version, "3.3.1");
+        assert_eq!(coordinate.classifier.as_deref(), Some("natives-linux"));
+        assert_eq!(coordinate.extension, "zip");
+        assert_eq!(coordinate.to_string(), "org.lwjgl:lwjgl:3.3.1:natives-linux@zip");
+        assert_eq!(coordinate.path(), "org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.zip");
+
+        let coordinate: MavenCoordinate = "net.fabricmc:fabric-loader:0.15.11".parse().unwrap();
+        assert_eq!(coordinate.to_string(), "net.fabricmc:fabric-loader:0.15.11");
+        assert_eq!(
+            coordinate.url("https://maven.fabricmc.net/"),
+            "https://maven.fabricmc.net/net/fabricmc/fabric-loader/0.15.11/fabric-loader-0.15.11.jar"
+        );
+
+        assert!("org.lwjgl:lwjgl".parse::<MavenCoordinate>().is_err());
+        assert!("org.lwjgl::3.3.1".parse::<MavenCoordinate>().is_err());
+        assert!("a:b:c:d:e".parse::<MavenCoordinate>().is_err());
+    }
+
+    #[test]
+    fn library_artifact() {
+        let library: LibraryItem = serde_json::from_str(
+            r#"{ "name": "net.fabricmc:intermediary:1.20.1", "url": "https://maven.fabricmc.net/", "sha1": "abc", "size": 10 }"#,
+        )
+        .unwrap();
+        let artifact = library.artifact().unwrap();
+        assert_eq!(artifact.path, "net/fabricmc/intermediary/1.20.1/intermediary-1.20.1.jar");
+        assert_eq!(artifact.url, "https://maven.fabricmc.net/net/fabricmc/intermediary/1.20.1/intermediary-1.20.1.jar");
+        assert_eq!(artifact.sha1.as_deref(), Some("abc"));
+
+        let library: LibraryItem = serde_json::from_str(r#"{ "name": "org.ow2.asm:asm:9.6" }"#).unwrap();
+        assert_eq!(library.artifact().unwrap().url, "https://libraries.minecraft.net/org/ow2/asm/asm/9.6/asm-9.6.jar");
+
+        let library: LibraryItem = serde_json::from_str(
+            r#"{
+                "name": "com.mojang:patchy:1.1",
+                "downloads": { "artifact": { "path": "com/mojang/patchy/1.1/patchy-1.1.jar", "sha1": "aef6", "size": 15817, "url": "https://libraries.minecraft.net/com/mojang/patchy/1.1/patchy-1.1.jar" } }
+            }"#,
+        )
+        .unwrap();
+        let artifact = library.artifact().unwrap();
+        assert_eq!(artifact.path, "com/mojang/patchy/1.1/patchy-1.1.jar");
+        assert_eq!(artifact.size, Some(15817));
+    }
+}
@@ -0,0 +1,181 @@
+//! Native library handling for versions that ship LWJGL natives as classifier jars.
+//!
+//! Before 1.19, libraries such as `lwjgl-platform` declare a `natives` map from OS name to the
+//! classifier holding the native binaries (e.g. `natives-windows-${arch}`). Those jars are not
+//! put on the classpath; they are unpacked into the natives directory passed to the game
+//! through `-Djava.library.path`, skipping the `extract.exclude` entries such as `META-INF/`.
+//! Newer versions ship natives as regular classpath artifacts and need none of this.
+//!
+//! # Example
+//! ```no_run
+//! use piston_mc::manifest_v2::ManifestV2;
+//! use piston_mc::rules::RuleEnvironment;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let manifest = ManifestV2::fetch().await.unwrap();
+//!     let version = manifest.version("1.12.2").await.unwrap().unwrap();
+//!     version
+//!         .extract_natives(".minecraft/libraries", ".minecraft/versions/1.12.2/natives", &RuleEnvironment::current())
+//!         .await
+//!         .unwrap();
+//! }
+//! ```
+
+use crate::maven::{LibraryArtifact, MavenCoordinate};
+use crate::rules::RuleEnvironment;
+#[cfg(feature = "downloads")]
+use crate::sha_validation::validate_file;
+#[cfg(feature = "downloads")]
+use crate::version_manifest::VersionManifest;
+use crate::version_manifest::{Download, LibraryItem};
+#[cfg(feature = "downloads")]
+use anyhow::Result;
+#[cfg(feature = "downloads")]
+use simple_download_utility::download_and_validate_file;
+#[cfg(feature = "downloads")]
+use std::path::{Path, PathBuf};
+
+impl LibraryItem {
+    /// Returns the natives classifier of this library for `environment`, with `${arch}` substituted.
+    pub fn native_classifier(&self, environment: &RuleEnvironment) -> Option<String> {
+        let classifier = self.natives.as_ref()?.get(&environment.os_name)?;
+        let arch = match environment.os_arch.as_str() {
+            "x86" => "32",
+            _ => "64",
+        };
+        Some(classifier.replace("${arch}", arch))
+    }
+
+    /// Returns the natives jar of this library for `environment`, if it has one.
+    pub fn native_download(&self, environment: &RuleEnvironment) -> Option<&Download> {
+        let classifier = self.native_classifier(environment)?;
+        self.downloads.as_ref()?.classifiers.as_ref()?.get(&classifier)
+    }
+
+    /// Returns the natives jar of this library for `environment` as a [`LibraryArtifact`].
+    pub fn native_artifact(&self, environment: &RuleEnvironment) -> Option<LibraryArtifact> {
+        let download = self.native_download(environment)?;
+        let path = match &download.path {
+            Some(path) => path.clone(),
+            None => MavenCoordinate { classifier: self.native_classifier(environment), ..self.coordinate().ok()? }.path(),
+        };
+        Some(LibraryArtifact { path, url: download.url.clone(), sha1: Some(download.sha1.clone()), size: Some(download.size) })
+    }
+}
+
+#[cfg(feature = "downloads")]
+impl VersionManifest {
+    /// Downloads the natives jars of every allowed library into `libraries_directory` and
+    /// extracts them into `natives_directory`.
+    ///
+    /// Jars that are already present with a matching sha1 are not downloaded again.
+    pub async fn extract_natives(
+        &self,
+        libraries_directory: impl AsRef<Path>,
+        natives_directory: impl AsRef<Path>,
+        environment: &RuleEnvironment,
+    ) -> Result<()> {
+        let libraries_directory = libraries_directory.as_ref();
+        let natives_directory = natives_directory.as_ref();
+        tokio::fs::create_dir_all(natives_directory).await?;
+
+        for library in self.libraries.iter().filter(|library| library.is_allowed(environment)) {
+            let Some(artifact) = library.native_artifact(environment) else {
+                continue;
+            };
+            let jar = libraries_directory.join(&artifact.path);
+            let sha1 = artifact.sha1.unwrap_or_default();
+            if !validate_file(&jar, &sha1) {
+                debug!("Downloading natives {}", jar.display());
+                download_and_validate_file(&artifact.url, &jar, &sha1, None).await?;
+            }
+
+            let exclude = library.extract.as_ref().map(|extract| extract.exclude.clone()).unwrap_or_default();
+            let destination = natives_directory.to_path_buf();
+            debug!("Extracting natives {} to {}", jar.display(), destination.display());
+            tokio::task::spawn_blocking(move || extract_jar(&jar, &destination, &exclude)).await??;
+        }
+
+        Ok(())
+    }
+}
+
+/// Unpacks every file of `jar` into `destination` whose name does not start with an `exclude` prefix.
+#[cfg(feature = "downloads")]
+fn extract_jar(jar: &Path, destination: &Path, exclude: &[String]) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(jar)?)?;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        let name = entry.name()?.to_string();
+        if entry.is_dir() || exclude.iter().any(|prefix| name.starts_with(prefix.as_str())) {
+            continue;
+        }
+        let Some(relative_path) = entry.enclosed_name() else {
+            warn!("Skipping unsafe natives entry '{}' in {}", name, jar.display());
+            continue;
+        };
+
+        let path: PathBuf = destination.join(relative_path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::File::create(&path)?;
+        std::io::copy(&mut entry, &mut file)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rules::RuleEnvironment;
+    use crate::version_manifest::LibraryItem;
+
+    const LIBRARY: &str = r#"{
+        "name": "tv.twitch:twitch-platform:6.5",
+        "downloads": {
+            "classifiers": {
+                "natives-linux": { "path": "linux.jar", "sha1": "", "size": 0, "url": "" },
+                "natives-windows-32": { "path": "windows-32.jar", "sha1": "", "size": 0, "url": "" },
+                "natives-windows-64": { "path": "windows-64.jar", "sha1": "", "size": 0, "url": "" }
+            }
+        },
+        "extract": { "exclude": ["META-INF/"] },
+        "natives": { "linux": "natives-linux", "windows": "natives-windows-${arch}" }
+    }"#;
+
+    #[test]
+    fn native_classifier() {
+        let library: LibraryItem = serde_json::from_str(LIBRARY).unwrap();
+        assert_eq!(library.native_classifier(&RuleEnvironment::new("windows", "x86")).as_deref(), Some("natives-windows-32"));
+        assert_eq!(library.native_classifier(&RuleEnvironment::new("windows", "x86_64")).as_deref(), Some("natives-windows-64"));
+        assert_eq!(library.native_classifier(&RuleEnvironment::new("osx", "x86_64")), None);
+
+        let download = library.native_download(&RuleEnvironment::new("linux", "x86_64")).unwrap();
+        assert_eq!(download.path.as_deref(), Some("linux.jar"));
+    }
+
+    #[test]
+    #[cfg(feature = "downloads")]
+    fn extract_jar() {
+        use std::io::Write;
+
+        let directory = std::path::Path::new("target/test/natives");
+        _ = std::fs::remove_dir_all(directory);
+        std::fs::create_dir_all(directory).unwrap();
+
+        let jar = directory.join("natives.jar");
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(&jar).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        writer.start_file("liblwjgl.so", options).unwrap();
+        writer.write_all(b"native").unwrap();
+        writer.start_file("META-INF/MANIFEST.MF", options).unwrap();
+        writer.write_all(b"Manifest-Version: 1.0").unwrap();
+        writer.finish().unwrap();
+
+        let output = directory.join("output");
+        super::extract_jar(&jar, &output, &["META-INF/".to_string()]).unwrap();
+        assert_eq!(std::fs::read(output.join("liblwjgl.so")).unwrap(), b"native");
+        assert!(!output.join("META-INF").exists());
+    }
+}
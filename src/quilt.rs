@@ -0,0 +1,140 @@
+//! Client for the Quilt meta API.
+//!
+//! Lists the Quilt loader versions available for a game version and fetches the launcher
+//! profile for a game and loader version pair. The profile is a child [`VersionManifest`] that
+//! inherits from the vanilla version, use [`VersionManifest::resolve`] to get a launchable manifest.
+//!
+//! # Example
+//! ```no_run
+//! use piston_mc::manifest_v2::ManifestV2;
+//! use piston_mc::quilt::QuiltMeta;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let quilt = QuiltMeta::default();
+//!     let loaders = quilt.loader_versions("1.20.1").await.unwrap();
+//!     let profile = quilt.profile("1.20.1", &loaders[0].loader.version).await.unwrap();
+//!     let version = profile.resolve(&ManifestV2::fetch().await.unwrap()).await.unwrap();
+//!     println!("{} launches {}", version.id, version.main_class);
+//! }
+//! ```
+
+use crate::http::fetch_json;
+use crate::version_manifest::VersionManifest;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+const QUILT_META_URL: &str = "https://meta.quiltmc.org/v3";
+
+/// Entry point to the Quilt meta API, [`QuiltMeta::default`] talks to the official instance.
+#[derive(Clone, Debug)]
+pub struct QuiltMeta {
+    base_url: String,
+}
+
+/// A loader version together with the mappings it uses for a specific game version.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct QuiltLoaderEntry {
+    pub loader: QuiltLoaderVersion,
+    pub hashed: QuiltMavenVersion,
+    pub intermediary: QuiltMavenVersion,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct QuiltLoaderVersion {
+    pub separator: String,
+    pub build: u32,
+    /// Maven coordinate of the loader, e.g. `org.quiltmc:quilt-loader:0.26.0`.
+    pub maven: String,
+    pub version: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct QuiltMavenVersion {
+    pub maven: String,
+    pub version: String,
+}
+
+impl Default for QuiltMeta {
+    fn default() -> Self {
+        Self::with_base_url(QUILT_META_URL)
+    }
+}
+
+impl QuiltMeta {
+    /// Talks to a Quilt meta instance other than the official one, e.g. a mirror.
+    pub fn with_base_url(base_url: impl AsRef<str>) -> Self {
+        Self { base_url: base_url.as_ref().trim_end_matches('/').to_string() }
+    }
+
+    /// Lists the loader versions available for `game_version`, newest first.
+    pub async fn loader_versions(&self, game_version: impl AsRef<str>) -> Result<Vec<QuiltLoaderEntry>> {
+        let url = format!("{}/versions/loader/{}", self.base_url, game_version.as_ref());
+        debug!("Fetching {}", url);
+        let loaders: Vec<QuiltLoaderEntry> = fetch_json(&url).await?;
+        debug!("Found {} loader versions for {}", loaders.len(), game_version.as_ref());
+        Ok(loaders)
+    }
+
+    /// Fetches the launcher profile of `loader_version` for `game_version`.
+    pub async fn profile(&self, game_version: impl AsRef<str>, loader_version: impl AsRef<str>) -> Result<VersionManifest> {
+        let url = format!("{}/versions/loader/{}/{}/profile/json", self.base_url, game_version.as_ref(), loader_version.as_ref());
+        VersionManifest::from_url(url).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::quilt::QuiltMeta;
+    use crate::serve_test_responses;
+    #[cfg(feature = "log")]
+    use crate::setup_logging;
+
+    #[tokio::test]
+    async fn loader_versions() {
+        #[cfg(feature = "log")]
+        setup_logging();
+        let loaders = r#"[{
+            "loader": { "separator": ".", "build": 0, "maven": "org.quiltmc:quilt-loader:0.26.0", "version": "0.26.0" },
+            "hashed": { "maven": "org.quiltmc:hashed:1.20.1", "version": "1.20.1" },
+            "intermediary": { "maven": "net.fabricmc:intermediary:1.20.1", "version": "1.20.1" },
+            "launcherMeta": { "version": 1, "libraries": { "client": [], "common": [], "server": [] } }
+        }]"#;
+        let base_url = serve_test_responses(vec![("/v3/versions/loader/1.20.1", loaders.to_string())]).await;
+        let quilt = QuiltMeta::with_base_url(format!("{}/v3", base_url));
+
+        let loaders = quilt.loader_versions("1.20.1").await.unwrap();
+        assert_eq!(loaders.len(), 1);
+        assert_eq!(loaders[0].loader.maven, "org.quiltmc:quilt-loader:0.26.0");
+        assert_eq!(loaders[0].intermediary.version, "1.20.1");
+        assert!(quilt.loader_versions("0.0.0").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn profile() {
+        #[cfg(feature = "log")]
+        setup_logging();
+        let profile = r#"{
+            "id": "quilt-loader-0.26.0-1.20.1",
+            "inheritsFrom": "1.20.1",
+            "type": "release",
+            "mainClass": "org.quiltmc.loader.impl.launch.knot.KnotClient",
+            "arguments": { "game": [] },
+            "libraries": [
+                { "name": "net.fabricmc:intermediary:1.20.1", "url": "https://maven.fabricmc.net/" },
+                { "name": "org.quiltmc:quilt-loader:0.26.0", "url": "https://maven.quiltmc.org/repository/release/" }
+            ],
+            "releaseTime": "2024-06-24T17:21:39.839Z",
+            "time": "2024-06-24T17:21:39.839Z"
+        }"#;
+        let base_url = serve_test_responses(vec![("/v3/versions/loader/1.20.1/0.26.0/profile/json", profile.to_string())]).await;
+        let quilt = QuiltMeta::with_base_url(format!("{}/v3", base_url));
+
+        let manifest = quilt.profile("1.20.1", "0.26.0").await.unwrap();
+        assert_eq!(manifest.inherits_from.as_deref(), Some("1.20.1"));
+        assert_eq!(
+            manifest.libraries[1].artifact().unwrap().url,
+            "https://maven.quiltmc.org/repository/release/org/quiltmc/quilt-loader/0.26.0/quilt-loader-0.26.0.jar"
+        );
+    }
+}
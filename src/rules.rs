@@ -0,0 +1,220 @@
+//! Evaluation of the `rules` attached to libraries and conditional arguments.
+//!
+//! Rules are evaluated the same way the official launcher does: an empty rule list always
+//! allows, otherwise everything starts out disallowed and every rule that matches the
+//! [`RuleEnvironment`] overrides the outcome with its own action, so the last matching rule wins.
+//!
+//! # Example
+//! ```
+//! use piston_mc::rules::{FEATURE_HAS_CUSTOM_RESOLUTION, RuleEnvironment};
+//!
+//! let environment = RuleEnvironment::current().with_feature(FEATURE_HAS_CUSTOM_RESOLUTION, true);
+//! assert!(environment.is_feature_enabled(FEATURE_HAS_CUSTOM_RESOLUTION));
+//! ```
+
+use crate::version_manifest::{ConditionalArgument, LibraryItem, OsRule, Rule, RuleAction};
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Set when the player does not own the game and should be launched in demo mode.
+pub const FEATURE_IS_DEMO_USER: &str = "is_demo_user";
+/// Set when the launcher passes an explicit window size.
+pub const FEATURE_HAS_CUSTOM_RESOLUTION: &str = "has_custom_resolution";
+/// Set when the launcher supports writing a quick play log.
+pub const FEATURE_HAS_QUICK_PLAYS_SUPPORT: &str = "has_quick_plays_support";
+/// Set when launching straight into a singleplayer world.
+pub const FEATURE_IS_QUICK_PLAY_SINGLEPLAYER: &str = "is_quick_play_singleplayer";
+/// Set when launching straight into a multiplayer server.
+pub const FEATURE_IS_QUICK_PLAY_MULTIPLAYER: &str = "is_quick_play_multiplayer";
+/// Set when launching straight into a realm.
+pub const FEATURE_IS_QUICK_PLAY_REALMS: &str = "is_quick_play_realms";
+
+/// The platform and launcher features that rules are evaluated against.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleEnvironment {
+    /// OS name as used by the manifests: `windows`, `osx` or `linux`.
+    pub os_name: String,
+    /// Architecture as used by the manifests: `x86`, `x86_64` or `arm64`.
+    pub os_arch: String,
+    /// OS version matched against [`OsRule::version`]; rules with a version pattern never match when this is `None`.
+    pub os_version: Option<String>,
+    /// Launcher features; features that are not present are treated as disabled.
+    pub features: HashMap<String, bool>,
+}
+
+impl RuleEnvironment {
+    pub fn new(os_name: impl Into<String>, os_arch: impl Into<String>) -> Self {
+        Self { os_name: os_name.into(), os_arch: os_arch.into(), os_version: None, features: HashMap::new() }
+    }
+
+    /// Describes the machine this code is running on, with no launcher features enabled.
+    pub fn current() -> Self {
+        let os_name = match std::env::consts::OS {
+            "macos" => "osx",
+            other => other,
+        };
+        let os_arch = match std::env::consts::ARCH {
+            "aarch64" => "arm64",
+            other => other,
+        };
+        let mut environment = Self::new(os_name, os_arch);
+        environment.os_version = current_os_version();
+        environment
+    }
+
+    pub fn with_os_version(mut self, os_version: impl Into<String>) -> Self {
+        self.os_version = Some(os_version.into());
+        self
+    }
+
+    pub fn with_feature(mut self, feature: impl Into<String>, enabled: bool) -> Self {
+        self.features.insert(feature.into(), enabled);
+        self
+    }
+
+    pub fn is_feature_enabled(&self, feature: &str) -> bool {
+        self.features.get(feature).copied().unwrap_or(false)
+    }
+}
+
+/// Returns whether a list of rules allows its library or argument in `environment`.
+pub fn is_allowed(rules: &[Rule], environment: &RuleEnvironment) -> bool {
+    if rules.is_empty() {
+        return true;
+    }
+
+    let mut action = RuleAction::Disallow;
+    for rule in rules {
+        if rule.matches(environment) {
+            action = rule.action;
+        }
+    }
+    action == RuleAction::Allow
+}
+
+impl Rule {
+    /// Returns whether every condition of this rule holds in `environment`, regardless of its action.
+    pub fn matches(&self, environment: &RuleEnvironment) -> bool {
+        if let Some(os) = &self.os
+            && !os.matches(environment)
+        {
+            return false;
+        }
+
+        if let Some(features) = &self.features {
+            return features.iter().all(|(feature, expected)| environment.is_feature_enabled(feature) == *expected);
+        }
+
+        true
+    }
+}
+
+impl OsRule {
+    pub fn matches(&self, environment: &RuleEnvironment) -> bool {
+        // Newer manifests qualify the name with the architecture, e.g. `osx-arm64`.
+        if let Some(name) = &self.name
+            && *name != environment.os_name
+            && *name != format!("{}-{}", environment.os_name, environment.os_arch)
+        {
+            return false;
+        }
+
+        if let Some(arch) = &self.arch
+            && *arch != environment.os_arch
+        {
+            return false;
+        }
+
+        if let Some(version) = &self.version {
+            let Some(os_version) = &environment.os_version else {
+                return false;
+            };
+            return match Regex::new(version) {
+                Ok(pattern) => pattern.is_match(os_version),
+                Err(_e) => {
+                    warn!("Invalid os version pattern '{}': {}", version, _e);
+                    false
+                }
+            };
+        }
+
+        true
+    }
+}
+
+impl ConditionalArgument {
+    pub fn is_allowed(&self, environment: &RuleEnvironment) -> bool {
+        is_allowed(&self.rules, environment)
+    }
+}
+
+impl LibraryItem {
+    pub fn is_allowed(&self, environment: &RuleEnvironment) -> bool {
+        self.rules.as_deref().is_none_or(|rules| is_allowed(rules, environment))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn current_os_version() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/osrelease").ok().map(|version| version.trim().to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn current_os_version() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rules::*;
+    use crate::version_manifest::{Rule, RuleAction};
+
+    fn rules(json: &str) -> Vec<Rule> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn empty_rules_allow() {
+        assert!(is_allowed(&[], &RuleEnvironment::new("linux", "x86_64")));
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let rules = rules(r#"[{ "action": "allow" }, { "action": "disallow", "os": { "name": "osx" } }]"#);
+        assert_eq!(rules[1].action, RuleAction::Disallow);
+        assert!(is_allowed(&rules, &RuleEnvironment::new("linux", "x86_64")));
+        assert!(!is_allowed(&rules, &RuleEnvironment::new("osx", "x86_64")));
+    }
+
+    #[test]
+    fn unmatched_allow_disallows() {
+        let rules = rules(r#"[{ "action": "allow", "os": { "name": "windows" } }]"#);
+        assert!(is_allowed(&rules, &RuleEnvironment::new("windows", "x86_64")));
+        assert!(!is_allowed(&rules, &RuleEnvironment::new("linux", "x86_64")));
+    }
+
+    #[test]
+    fn os_arch_and_version() {
+        let arch = rules(r#"[{ "action": "allow", "os": { "arch": "x86" } }]"#);
+        assert!(is_allowed(&arch, &RuleEnvironment::new("windows", "x86")));
+        assert!(!is_allowed(&arch, &RuleEnvironment::new("windows", "x86_64")));
+
+        let version = rules(r#"[{ "action": "allow", "os": { "name": "windows", "version": "^10\\." } }]"#);
+        assert!(is_allowed(&version, &RuleEnvironment::new("windows", "x86_64").with_os_version("10.0")));
+        assert!(!is_allowed(&version, &RuleEnvironment::new("windows", "x86_64").with_os_version("6.1")));
+        assert!(!is_allowed(&version, &RuleEnvironment::new("windows", "x86_64")));
+
+        let qualified = rules(r#"[{ "action": "allow", "os": { "name": "osx-arm64" } }]"#);
+        assert!(is_allowed(&qualified, &RuleEnvironment::new("osx", "arm64")));
+        assert!(!is_allowed(&qualified, &RuleEnvironment::new("osx", "x86_64")));
+    }
+
+    #[test]
+    fn features() {
+        let rules = rules(r#"[{ "action": "allow", "features": { "has_custom_resolution": true } }]"#);
+        let environment = RuleEnvironment::new("linux", "x86_64");
+        assert!(!is_allowed(&rules, &environment));
+        assert!(is_allowed(&rules, &environment.clone().with_feature(FEATURE_HAS_CUSTOM_RESOLUTION, true)));
+        assert!(!is_allowed(&rules, &environment.with_feature(FEATURE_HAS_CUSTOM_RESOLUTION, false)));
+    }
+}
@@ -0,0 +1,235 @@
+//! Discovery of java installations that are already present on the system.
+//!
+//! [`JavaDiscovery`] looks at `JAVA_HOME`, the `PATH` and the directories JDKs are usually
+//! installed to, and describes every installation it finds by its `release` file. A version can
+//! then run on a system java instead of a downloaded runtime:
+//!
+//! ```no_run
+//! use piston_mc::system_java::JavaDiscovery;
+//! use piston_mc::version_manifest::JavaVersion;
+//!
+//! let java_version = JavaVersion { component: "java-runtime-gamma".to_string(), major_version: 17 };
+//! if let Some(java) = JavaDiscovery::default().find(&java_version) {
+//!     println!("Using {} {} at {}", java.vendor.unwrap_or_default(), java.version, java.executable.display());
+//! }
+//! ```
+
+use crate::version_manifest::JavaVersion;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A java installation described by the `release` file in its home directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SystemJava {
+    pub home: PathBuf,
+    pub executable: PathBuf,
+    /// The `IMPLEMENTOR` of the installation, e.g. `Eclipse Adoptium`.
+    pub vendor: Option<String>,
+    /// The full `JAVA_VERSION`, e.g. `17.0.8` or `1.8.0_392`.
+    pub version: String,
+    pub major_version: u8,
+    /// The `OS_ARCH` the installation was built for, e.g. `x86_64` or `aarch64`.
+    pub architecture: Option<String>,
+}
+
+/// Where to look for java installations, [`JavaDiscovery::default`] covers the usual locations.
+#[derive(Clone, Debug)]
+pub struct JavaDiscovery {
+    environment: bool,
+    directories: Vec<PathBuf>,
+}
+
+impl Default for JavaDiscovery {
+    fn default() -> Self {
+        let directories: &[&str] = if cfg!(target_os = "windows") {
+            &["C:\\Program Files\\Java", "C:\\Program Files\\Eclipse Adoptium", "C:\\Program Files\\Microsoft", "C:\\Program Files\\Zulu"]
+        } else if cfg!(target_os = "macos") {
+            &["/Library/Java/JavaVirtualMachines"]
+        } else {
+            &["/usr/lib/jvm", "/usr/lib64/jvm", "/usr/java", "/opt/java"]
+        };
+        Self { environment: true, directories: directories.iter().map(PathBuf::from).collect() }
+    }
+}
+
+impl JavaDiscovery {
+    /// A discovery without any location, add them with [`JavaDiscovery::environment`] and [`JavaDiscovery::directory`].
+    pub fn new() -> Self {
+        Self { environment: false, directories: vec![] }
+    }
+
+    /// Sets whether `JAVA_HOME` and the `PATH` are searched.
+    pub fn environment(mut self, environment: bool) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Adds a directory whose subdirectories are java homes, like `/usr/lib/jvm`.
+    pub fn directory(mut self, directory: impl AsRef<Path>) -> Self {
+        self.directories.push(directory.as_ref().to_path_buf());
+        self
+    }
+
+    /// Returns every installation found, each home only once.
+    pub fn discover(&self) -> Vec<SystemJava> {
+        let mut homes = vec![];
+        if self.environment {
+            if let Some(java_home) = std::env::var_os("JAVA_HOME") {
+                homes.push(PathBuf::from(java_home));
+            }
+            if let Some(path) = std::env::var_os("PATH") {
+                homes.extend(std::env::split_paths(&path).filter_map(|directory| {
+                    // `/usr/bin/java` usually links into the actual installation.
+                    let executable = directory.join(executable_name()).canonicalize().ok()?;
+                    Some(executable.parent()?.parent()?.to_path_buf())
+                }));
+            }
+        }
+        for directory in &self.directories {
+            let Ok(entries) = std::fs::read_dir(directory) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                homes.push(entry.path());
+                // macOS bundles keep the home inside the bundle.
+                homes.push(entry.path().join("Contents").join("Home"));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        homes
+            .into_iter()
+            .filter_map(|home| home.canonicalize().ok())
+            .map(jdk_home)
+            .filter(|home| seen.insert(home.clone()))
+            .filter_map(SystemJava::from_home)
+            .collect()
+    }
+
+    /// Returns the first installation that runs `java_version`, see [`SystemJava::is_compatible`].
+    pub fn find(&self, java_version: &JavaVersion) -> Option<SystemJava> {
+        self.discover().into_iter().find(|java| java.is_compatible(java_version))
+    }
+}
+
+impl SystemJava {
+    /// Describes the installation in `home`, or `None` if it has no `release` file or java executable.
+    ///
+    /// The `jre` directory of a java 8 JDK describes the JDK it is part of.
+    pub fn from_home(home: impl AsRef<Path>) -> Option<Self> {
+        let home = jdk_home(home.as_ref().to_path_buf());
+        let home = home.as_path();
+        let executable = home.join("bin").join(executable_name());
+        if !executable.is_file() {
+            return None;
+        }
+        let mut release = parse_release(&std::fs::read_to_string(home.join("release")).ok()?);
+        let version = release.remove("JAVA_VERSION")?;
+
+        Some(Self {
+            home: home.to_path_buf(),
+            executable,
+            vendor: release.remove("IMPLEMENTOR"),
+            major_version: major_version(&version)?,
+            version,
+            architecture: release.remove("OS_ARCH"),
+        })
+    }
+
+    /// Whether this installation has the major version `java_version` asks for.
+    ///
+    /// Newer majors are not considered compatible: old versions and mod loaders break on them.
+    pub fn is_compatible(&self, java_version: &JavaVersion) -> bool {
+        self.major_version == java_version.major_version
+    }
+}
+
+fn executable_name() -> String {
+    format!("java{}", std::env::consts::EXE_SUFFIX)
+}
+
+/// Returns the JDK a java 8 `jre` directory belongs to, `java` on the PATH resolves into it.
+/// Any other home is returned unchanged.
+fn jdk_home(home: PathBuf) -> PathBuf {
+    match home.parent() {
+        Some(jdk) if home.ends_with("jre") && !home.join("release").is_file() && jdk.join("release").is_file() => jdk.to_path_buf(),
+        _ => home,
+    }
+}
+
+/// Parses the `KEY="value"` lines of a `release` file.
+fn parse_release(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().trim_matches('"').to_string()))
+        .collect()
+}
+
+/// Returns the major version of a `JAVA_VERSION`, which is the second number for java 8 and older.
+fn major_version(version: &str) -> Option<u8> {
+    let mut parts = version.split(['.', '_', '-', '+']);
+    match parts.next()? {
+        "1" => parts.next()?.parse().ok(),
+        major => major.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::system_java::*;
+
+    fn create_home(home: &Path, release: &str) {
+        std::fs::create_dir_all(home.join("bin")).unwrap();
+        std::fs::write(home.join("bin").join(executable_name()), b"").unwrap();
+        std::fs::write(home.join("release"), release).unwrap();
+    }
+
+    #[test]
+    fn discover() {
+        let directory = Path::new("target/test/system-java");
+        _ = std::fs::remove_dir_all(directory);
+        create_home(&directory.join("temurin-17"), "IMPLEMENTOR=\"Eclipse Adoptium\"\nJAVA_VERSION=\"17.0.8\"\nOS_ARCH=\"x86_64\"\n");
+        create_home(&directory.join("java-8-openjdk"), "JAVA_VERSION=\"1.8.0_392\"\n");
+        std::fs::create_dir_all(directory.join("not-a-jdk")).unwrap();
+
+        let mut installations = JavaDiscovery::new().directory(directory).discover();
+        installations.sort_by_key(|java| java.major_version);
+        assert_eq!(installations.len(), 2);
+        assert_eq!(installations[0].version, "1.8.0_392");
+        assert_eq!(installations[0].major_version, 8);
+        assert_eq!(installations[0].vendor, None);
+        assert_eq!(installations[1].vendor.as_deref(), Some("Eclipse Adoptium"));
+        assert_eq!(installations[1].architecture.as_deref(), Some("x86_64"));
+        assert!(installations[1].executable.ends_with(Path::new("temurin-17/bin").join(executable_name())));
+
+        let discovery = JavaDiscovery::new().directory(directory);
+        let gamma = JavaVersion { component: "java-runtime-gamma".to_string(), major_version: 17 };
+        assert_eq!(discovery.find(&gamma).unwrap().major_version, 17);
+        assert_eq!(discovery.find(&JavaVersion::default()).unwrap().major_version, 8);
+        assert!(discovery.find(&JavaVersion { component: "java-runtime-delta".to_string(), major_version: 21 }).is_none());
+    }
+
+    #[test]
+    fn java_8_jre() {
+        let jdk = Path::new("target/test/system-java-8/jdk8u392");
+        _ = std::fs::remove_dir_all(jdk);
+        create_home(jdk, "JAVA_VERSION=\"1.8.0_392\"\n");
+        std::fs::create_dir_all(jdk.join("jre/bin")).unwrap();
+        std::fs::write(jdk.join("jre/bin").join(executable_name()), b"").unwrap();
+
+        let java = SystemJava::from_home(jdk.join("jre")).unwrap();
+        assert_eq!(java.home, jdk);
+        assert_eq!(java.major_version, 8);
+        assert_eq!(JavaDiscovery::new().directory(jdk.parent().unwrap()).discover().len(), 1);
+    }
+
+    #[test]
+    fn versions() {
+        assert_eq!(major_version("17.0.8"), Some(17));
+        assert_eq!(major_version("21"), Some(21));
+        assert_eq!(major_version("1.8.0_392"), Some(8));
+        assert_eq!(major_version("11.0.20+8"), Some(11));
+        assert_eq!(major_version("unknown"), None);
+    }
+}
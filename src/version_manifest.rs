@@ -3,41 +3,74 @@
 #[cfg(feature = "assets")]
 use crate::assets::Assets;
 #[cfg(feature = "downloads")]
-use simple_download_utility::{DownloadProgress, download_and_validate_file, download_file};
+use crate::rules::RuleEnvironment;
+#[cfg(feature = "downloads")]
+use simple_download_utility::{
+    DownloadProgress, FileDownloadArguments, MultiDownloadProgress, download_and_validate_file, download_file, download_multiple_files,
+};
+#[cfg(feature = "downloads")]
+use crate::http::fetch_json;
+#[cfg(feature = "downloads")]
+use crate::game_directory::GameDirectory;
+#[cfg(feature = "downloads")]
+use crate::manifest_v2::ManifestV2;
+#[cfg(feature = "downloads")]
+use crate::sha_validation::validate_file;
 use crate::manifest_v2::ReleaseType;
 #[cfg(any(feature = "downloads", feature = "assets"))]
 use anyhow::Result;
 #[cfg(feature = "downloads")]
 use anyhow::anyhow;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
 #[cfg(feature = "downloads")]
 use std::path::Path;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct VersionManifest {
     pub id: String,
+    /// Id of the version this one is based on; set by mod loader profiles, which only contain
+    /// what they add to or override in their parent. See [`VersionManifest::merge`].
+    #[serde(rename = "inheritsFrom", skip_serializing_if = "Option::is_none")]
+    pub inherits_from: Option<String>,
     #[serde(rename = "mainClass")]
     pub main_class: String,
-    #[serde(rename = "minimumLauncherVersion")]
+    #[serde(rename = "minimumLauncherVersion", default)]
     pub minimal_launcher_version: u8,
-    #[serde(rename = "releaseTime")]
+    #[serde(rename = "releaseTime", serialize_with = "serialize_time")]
     pub release_time: chrono::DateTime<chrono::Utc>,
+    #[serde(serialize_with = "serialize_time")]
     pub time: chrono::DateTime<chrono::Utc>,
     #[serde(rename = "type")]
     pub release_type: ReleaseType,
-    #[serde(alias = "minecraftArguments")]
+    /// Read from `arguments`, or from `minecraftArguments` on pre-1.13 versions, see [`Arguments`].
+    #[serde(flatten, with = "argument_keys")]
     pub arguments: Arguments,
-    #[serde(rename = "assetIndex")]
-    pub asset_index: AssetIndex,
-    pub assets: String,
+    #[serde(rename = "assetIndex", skip_serializing_if = "Option::is_none")]
+    pub asset_index: Option<AssetIndex>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assets: Option<String>,
     #[serde(rename = "complianceLevel", skip_serializing_if = "Option::is_none")]
     pub compliance_level: Option<u8>,
-    pub downloads: Downloads,
-    #[serde(rename = "javaVersion")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downloads: Option<Downloads>,
+    #[serde(rename = "javaVersion", skip_serializing_if = "Option::is_none")]
     pub java_version: Option<JavaVersion>,
+    #[serde(default)]
+    pub libraries: Vec<LibraryItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logging: Option<Logging>,
+    /// Keys this crate does not model, kept so a written manifest stays readable by other launchers.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
+/// The launch arguments of a version.
+///
+/// Manifests carry them under `arguments` since 1.13 and under `minecraftArguments` before.
+/// Some launcher generated and modded profiles carry both keys; `arguments` is used when it has
+/// game arguments, otherwise `minecraftArguments`.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(untagged)]
 pub enum Arguments {
@@ -45,9 +78,56 @@ pub enum Arguments {
     Pre113(String),
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl Default for Arguments {
+    fn default() -> Self {
+        Arguments::Post113(Post113::default())
+    }
+}
+
+/// Writes a timestamp the way Mojang does, e.g. `2023-06-12T13:25:51+00:00`.
+fn serialize_time<S: Serializer>(time: &chrono::DateTime<chrono::Utc>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&time.to_rfc3339())
+}
+
+/// (De)serializes [`Arguments`] from and into the `arguments` and `minecraftArguments` keys of a manifest.
+mod argument_keys {
+    use super::{Arguments, Post113};
+    use serde::ser::SerializeMap;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    struct ArgumentKeys {
+        #[serde(default)]
+        arguments: Option<Post113>,
+        #[serde(rename = "minecraftArguments", default)]
+        minecraft_arguments: Option<String>,
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arguments, D::Error> {
+        let keys = ArgumentKeys::deserialize(deserializer)?;
+        Ok(match (keys.arguments, keys.minecraft_arguments) {
+            (Some(arguments), Some(minecraft_arguments)) if arguments.game.is_empty() => Arguments::Pre113(minecraft_arguments),
+            (Some(arguments), _) => Arguments::Post113(arguments),
+            (None, Some(minecraft_arguments)) => Arguments::Pre113(minecraft_arguments),
+            (None, None) => Arguments::default(),
+        })
+    }
+
+    pub fn serialize<S: Serializer>(arguments: &Arguments, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        match arguments {
+            Arguments::Post113(arguments) => map.serialize_entry("arguments", arguments)?,
+            Arguments::Pre113(arguments) => map.serialize_entry("minecraftArguments", arguments)?,
+        }
+        map.end()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Post113 {
+    #[serde(default)]
     pub game: Vec<GameArgument>,
+    #[serde(default)]
     pub jvm: Vec<GameArgument>,
 }
 
@@ -77,17 +157,30 @@ pub enum ArgumentValue {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Rule {
-    pub action: String,
-    #[serde(default)]
+    pub action: RuleAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub features: Option<HashMap<String, bool>>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub os: Option<OsRule>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleAction {
+    #[serde(rename = "allow")]
+    Allow,
+    #[serde(rename = "disallow")]
+    Disallow,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct OsRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub arch: Option<String>,
+    /// Regular expression matched against the OS version, e.g. `^10\.` for Windows 10.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AssetIndex {
@@ -101,12 +194,20 @@ pub struct AssetIndex {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Downloads {
     pub client: Download,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub server: Option<Download>,
+    /// Downloads this crate does not use, such as `client_mappings`.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Download {
-    #[serde(skip_serializing_if = "Option::is_none", alias = "path")]
+    /// File name of a logging configuration.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
+    /// Path of a library artifact inside the libraries directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
     pub sha1: String,
     pub size: u64,
     pub url: String,
@@ -119,6 +220,13 @@ pub struct JavaVersion {
     pub major_version: u8,
 }
 
+/// The runtime versions without a `javaVersion` run on: `jre-legacy`, java 8.
+impl Default for JavaVersion {
+    fn default() -> Self {
+        Self { component: "jre-legacy".to_string(), major_version: 8 }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Logging {
     pub client: ClientLogging,
@@ -133,41 +241,47 @@ pub struct ClientLogging {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct LibraryItem {
     pub name: String,
-    pub downloads: LibraryDownload,
+    /// Missing on libraries added by mod loaders, which are resolved from `name` and `url` instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downloads: Option<LibraryDownload>,
+    /// Base url of the maven repository hosting this library when it has no `downloads`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Maps an OS name (`windows`, `osx`, `linux`) to the classifier holding its natives,
+    /// e.g. `natives-windows-${arch}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub natives: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extract: Option<LibraryExtract>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rules: Option<Vec<Rule>>,
+    /// Keys this crate does not model, such as the checksums mod loaders add.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct LibraryDownload {
-    pub name: String,
-    pub artifact: Download,
+    /// Missing on pre-1.19 natives-only libraries, which only ship `classifiers`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<Download>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub classifiers: Option<HashMap<String, Download>>,
+}
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LibraryExtract {
+    /// Path prefixes inside the natives jar that must not be extracted, e.g. `META-INF/`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
 }
 
 #[cfg(feature = "downloads")]
 impl VersionManifest {
     pub async fn from_url(url: impl AsRef<str>) -> Result<Self> {
-        let url = url.as_ref();
-        let response = reqwest::get(url).await?;
-        let text = response.text().await?;
-        let json_result = serde_json::from_str::<Self>(&text);
-
-        #[cfg(feature = "log")]
-        if let Err(ref e) = json_result {
-            let line = e.line();
-            let column = e.column();
-            error!("Failed to deserialize VersionManifest from {}: {}", url, e);
-            error!("Error at line {}, column {}", line, column);
-
-            // Show context around the error (60 chars before and after)
-            let error_offset = text.lines().take(line - 1).map(|l| l.len() + 1).sum::<usize>() + column - 1;
-            let start = error_offset.saturating_sub(60);
-            let end = (error_offset + 60).min(text.len());
-            let context = &text[start..end];
-
-            error!("Context around error: {}", context);
-        }
-
-        Ok(json_result?)
+        fetch_json(url.as_ref()).await
     }
 
     pub async fn download_client(
@@ -177,8 +291,9 @@ impl VersionManifest {
         sender: Option<tokio::sync::mpsc::Sender<DownloadProgress>>,
     ) -> Result<()> {
         let path = path.as_ref();
-        let url = &self.downloads.client.url;
-        let hash = &self.downloads.client.sha1;
+        let client = &self.downloads.as_ref().ok_or_else(|| anyhow!("No client download available"))?.client;
+        let url = &client.url;
+        let hash = &client.sha1;
 
         if validate {
             download_and_validate_file(url, path, hash, sender).await?;
@@ -196,7 +311,7 @@ impl VersionManifest {
         sender: Option<tokio::sync::mpsc::Sender<DownloadProgress>>,
     ) -> Result<()> {
         let path = path.as_ref();
-        if let Some(server) = &self.downloads.server {
+        if let Some(server) = self.downloads.as_ref().and_then(|downloads| downloads.server.as_ref()) {
             let url = &server.url;
             let hash = &server.sha1;
 
@@ -211,12 +326,177 @@ impl VersionManifest {
 
         Ok(())
     }
+
+    /// Writes this manifest to `versions/<id>/<id>.json` and downloads the client jar next to it.
+    ///
+    /// The jar is only downloaded when it is missing or its sha1 does not match.
+    pub async fn install_client(&self, game_directory: &GameDirectory, sender: Option<tokio::sync::mpsc::Sender<DownloadProgress>>) -> Result<()> {
+        self.write_manifest(game_directory).await?;
+
+        let path = game_directory.client_jar(&self.id);
+        let client = &self.downloads.as_ref().ok_or_else(|| anyhow!("No client download available"))?.client;
+        if !validate_file(&path, &client.sha1) {
+            self.download_client(&path, true, sender).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes this manifest to `versions/<id>/<id>.json` of `game_directory`, in the schema Mojang
+    /// publishes it in so the official launcher and other launchers can read it.
+    pub async fn write_manifest(&self, game_directory: &GameDirectory) -> Result<()> {
+        tokio::fs::create_dir_all(game_directory.version(&self.id)).await?;
+        tokio::fs::write(game_directory.version_manifest(&self.id), serde_json::to_string(self)?).await?;
+        Ok(())
+    }
+
+    /// Resolves `inherits_from` through `manifest`, returning the effective manifest of this version.
+    ///
+    /// Manifests that do not inherit from another version are returned unchanged.
+    pub async fn resolve(self, manifest: &ManifestV2) -> Result<VersionManifest> {
+        let mut resolved = self;
+        while let Some(parent_id) = resolved.inherits_from.clone() {
+            debug!("Resolving parent {} of {}", parent_id, resolved.id);
+            let parent = manifest.version(&parent_id).await?.ok_or_else(|| anyhow!("Parent version '{}' of '{}' not found", parent_id, resolved.id))?;
+            resolved = resolved.merge(parent);
+        }
+        Ok(resolved)
+    }
+
+    /// Downloads every library allowed on the current platform into `directory`, laid out in the
+    /// maven path structure, including the natives jars pre-1.19 versions extract at launch.
+    pub async fn download_libraries(
+        &self,
+        directory: impl AsRef<Path>,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<()> {
+        let download_items = self.library_downloads(directory.as_ref(), &RuleEnvironment::current());
+        debug!("Downloading {} libraries for {}", download_items.len(), self.id);
+        download_multiple_files(download_items, parallel, sender).await?;
+        Ok(())
+    }
+
+    /// Downloads the libraries into the `libraries` directory of `game_directory`, see [`VersionManifest::download_libraries`].
+    pub async fn install_libraries(
+        &self,
+        game_directory: &GameDirectory,
+        parallel: u16,
+        sender: Option<tokio::sync::mpsc::Sender<MultiDownloadProgress>>,
+    ) -> Result<()> {
+        self.download_libraries(game_directory.libraries(), parallel, sender).await
+    }
+
+    pub(crate) fn library_downloads(&self, directory: &Path, environment: &RuleEnvironment) -> Vec<FileDownloadArguments> {
+        let mut paths = HashSet::new();
+        self.libraries
+            .iter()
+            .filter(|library| library.is_allowed(environment))
+            .flat_map(|library| [library.artifact(), library.native_artifact(environment)])
+            .flatten()
+            .filter_map(|artifact| {
+                let path = directory.join(&artifact.path);
+                // The same jar can be listed more than once, it must only be downloaded once.
+                paths.insert(path.clone()).then(|| FileDownloadArguments {
+                    url: artifact.url,
+                    sha1: artifact.sha1,
+                    sender: None,
+                    path: path.to_string_lossy().into_owned(),
+                })
+            })
+            .collect()
+    }
+
+    /// Downloads the client log4j configuration into `<assets_directory>/log_configs/` and returns
+    /// the logging JVM argument pointing at it, or `None` if this version has no logging configuration.
+    ///
+    /// The file is only downloaded when it is missing or its sha1 does not match.
+    pub async fn download_log_config(&self, assets_directory: impl AsRef<Path>) -> Result<Option<String>> {
+        let Some(logging) = &self.logging else {
+            return Ok(None);
+        };
+        let file = &logging.client.file;
+        let name = file.id.as_ref().ok_or_else(|| anyhow!("Logging configuration has no id"))?;
+        let path = assets_directory.as_ref().join("log_configs").join(name);
+
+        if !validate_file(&path, &file.sha1) {
+            debug!("Downloading logging configuration {}", name);
+            download_and_validate_file(&file.url, &path, &file.sha1, None).await?;
+        }
+
+        Ok(Some(logging.client.jvm_argument(&path)))
+    }
+
+    /// Downloads the client log4j configuration into `assets/log_configs` of `game_directory`, see [`VersionManifest::download_log_config`].
+    pub async fn install_log_config(&self, game_directory: &GameDirectory) -> Result<Option<String>> {
+        self.download_log_config(game_directory.assets()).await
+    }
+}
+
+impl VersionManifest {
+    /// Merges this manifest on top of `parent`, the manifest named by `inherits_from`.
+    ///
+    /// Everything this manifest sets wins over the parent. Arguments are appended to the
+    /// parent's, unless this manifest uses the pre-1.13 format which replaces them entirely.
+    /// Libraries of this manifest come first and replace parent libraries with the same group,
+    /// artifact and classifier, so a mod loader can swap in its own version of a library.
+    pub fn merge(self, parent: VersionManifest) -> VersionManifest {
+        let arguments = match (self.arguments, parent.arguments) {
+            (Arguments::Pre113(arguments), _) => Arguments::Pre113(arguments),
+            (Arguments::Post113(child), parent) if child.game.is_empty() && child.jvm.is_empty() => parent,
+            (Arguments::Post113(child), parent) => {
+                let mut arguments = parent.to_post113();
+                arguments.game.extend(child.game);
+                arguments.jvm.extend(child.jvm);
+                Arguments::Post113(arguments)
+            }
+        };
+
+        let overridden: HashSet<String> = self.libraries.iter().map(library_key).collect();
+        let mut libraries = self.libraries;
+        libraries.extend(parent.libraries.into_iter().filter(|library| !overridden.contains(&library_key(library))));
+
+        VersionManifest {
+            id: self.id,
+            inherits_from: parent.inherits_from,
+            main_class: self.main_class,
+            minimal_launcher_version: self.minimal_launcher_version.max(parent.minimal_launcher_version),
+            release_time: self.release_time,
+            time: self.time,
+            release_type: self.release_type,
+            arguments,
+            asset_index: self.asset_index.or(parent.asset_index),
+            assets: self.assets.or(parent.assets),
+            compliance_level: self.compliance_level.or(parent.compliance_level),
+            downloads: self.downloads.or(parent.downloads),
+            java_version: self.java_version.or(parent.java_version),
+            libraries,
+            logging: self.logging.or(parent.logging),
+            extra: parent.extra.into_iter().chain(self.extra).collect(),
+        }
+    }
+}
+
+/// Identifies a library regardless of its version, falling back to the raw name if it is not a maven coordinate.
+fn library_key(library: &LibraryItem) -> String {
+    match library.coordinate() {
+        Ok(coordinate) => format!("{}:{}:{}", coordinate.group, coordinate.artifact, coordinate.classifier.unwrap_or_default()),
+        Err(_) => library.name.clone(),
+    }
+}
+
+impl ClientLogging {
+    /// Returns the JVM argument pointing log4j at the configuration file at `path`.
+    pub fn jvm_argument(&self, path: impl AsRef<std::path::Path>) -> String {
+        self.argument.replace("${path}", &path.as_ref().to_string_lossy())
+    }
 }
 
 #[cfg(feature = "assets")]
 impl VersionManifest {
     pub async fn assets(&self) -> Result<Assets> {
-        Assets::from_url(&self.asset_index.url).await
+        let asset_index = self.asset_index.as_ref().ok_or_else(|| anyhow!("No asset index available"))?;
+        Assets::from_url(&asset_index.url).await
     }
 }
 
@@ -226,6 +506,313 @@ mod test {
     #[cfg(feature = "log")]
     use crate::setup_logging;
 
+    const LEGACY_MANIFEST: &str = r#"{
+        "id": "1.12.2",
+        "mainClass": "net.minecraft.client.main.Main",
+        "minimumLauncherVersion": 18,
+        "releaseTime": "2017-09-18T08:39:46+00:00",
+        "time": "2017-09-18T08:39:46+00:00",
+        "type": "release",
+        "minecraftArguments": "--username ${auth_player_name} --version ${version_name} --gameDir ${game_directory} --assetsDir ${assets_root} --assetIndex ${assets_index_name} --uuid ${auth_uuid} --accessToken ${auth_access_token} --userType ${user_type} --versionType ${version_type}",
+        "assetIndex": {
+            "id": "1.12",
+            "sha1": "1584b57c1a0b5e593fad1f5b8f78536ca640547b",
+            "size": 143138,
+            "totalSize": 129336389,
+            "url": "https://launchermeta.mojang.com/v1/packages/1584b57c1a0b5e593fad1f5b8f78536ca640547b/1.12.json"
+        },
+        "assets": "1.12",
+        "downloads": {
+            "client": {
+                "sha1": "0f275bc1547d01fa5f56ba34bdc87d981ee12daf",
+                "size": 10180113,
+                "url": "https://launcher.mojang.com/v1/objects/0f275bc1547d01fa5f56ba34bdc87d981ee12daf/client.jar"
+            }
+        },
+        "javaVersion": { "component": "jre-legacy", "majorVersion": 8 },
+        "logging": {
+            "client": {
+                "argument": "-Dlog4j.configurationFile=${path}",
+                "file": {
+                    "id": "client-1.12.xml",
+                    "sha1": "bd65e7d2e3c237be76cfbef4c2405033d7f91521",
+                    "size": 888,
+                    "url": "https://launcher.mojang.com/v1/objects/bd65e7d2e3c237be76cfbef4c2405033d7f91521/client-1.12.xml"
+                },
+                "type": "log4j2-xml"
+            }
+        },
+        "libraries": [
+            {
+                "name": "com.mojang:patchy:1.1",
+                "downloads": {
+                    "artifact": {
+                        "path": "com/mojang/patchy/1.1/patchy-1.1.jar",
+                        "sha1": "aef610b34a1be37fa851825f12372b78424d8903",
+                        "size": 15817,
+                        "url": "https://libraries.minecraft.net/com/mojang/patchy/1.1/patchy-1.1.jar"
+                    }
+                }
+            },
+            {
+                "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.4-nightly-20150209",
+                "downloads": {
+                    "artifact": {
+                        "path": "org/lwjgl/lwjgl/lwjgl-platform/2.9.4-nightly-20150209/lwjgl-platform-2.9.4-nightly-20150209.jar",
+                        "sha1": "b04f3ee8f5e43fa3b162981b50bb72fe1acabb33",
+                        "size": 22,
+                        "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl/lwjgl-platform/2.9.4-nightly-20150209/lwjgl-platform-2.9.4-nightly-20150209.jar"
+                    },
+                    "classifiers": {
+                        "natives-linux": {
+                            "path": "org/lwjgl/lwjgl/lwjgl-platform/2.9.4-nightly-20150209/lwjgl-platform-2.9.4-nightly-20150209-natives-linux.jar",
+                            "sha1": "931074f46c795d2f7b30ed6395df5715cfd7675b",
+                            "size": 578680,
+                            "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl/lwjgl-platform/2.9.4-nightly-20150209/lwjgl-platform-2.9.4-nightly-20150209-natives-linux.jar"
+                        },
+                        "natives-windows": {
+                            "path": "org/lwjgl/lwjgl/lwjgl-platform/2.9.4-nightly-20150209/lwjgl-platform-2.9.4-nightly-20150209-natives-windows.jar",
+                            "sha1": "d4f3b6b4e45f1e2ba8b1b82d5f4c1a3d0aef1f07",
+                            "size": 613748,
+                            "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl/lwjgl-platform/2.9.4-nightly-20150209/lwjgl-platform-2.9.4-nightly-20150209-natives-windows.jar"
+                        }
+                    }
+                },
+                "extract": { "exclude": ["META-INF/"] },
+                "natives": { "linux": "natives-linux", "windows": "natives-windows" },
+                "rules": [{ "action": "allow" }, { "action": "disallow", "os": { "name": "osx" } }]
+            },
+            {
+                "name": "tv.twitch:twitch-platform:6.5:natives-windows-${arch}",
+                "downloads": {
+                    "classifiers": {
+                        "natives-windows-32": {
+                            "path": "tv/twitch/twitch-platform/6.5/twitch-platform-6.5-natives-windows-32.jar",
+                            "sha1": "206c4ccaecdbcfd2a1631150c69a97bbc9c20c11",
+                            "size": 474225,
+                            "url": "https://libraries.minecraft.net/tv/twitch/twitch-platform/6.5/twitch-platform-6.5-natives-windows-32.jar"
+                        },
+                        "natives-windows-64": {
+                            "path": "tv/twitch/twitch-platform/6.5/twitch-platform-6.5-natives-windows-64.jar",
+                            "sha1": "9fdd0fd5aed0817063dcf95b69349a171f447ebd",
+                            "size": 580098,
+                            "url": "https://libraries.minecraft.net/tv/twitch/twitch-platform/6.5/twitch-platform-6.5-natives-windows-64.jar"
+                        }
+                    }
+                },
+                "extract": { "exclude": ["META-INF/"] },
+                "natives": { "windows": "natives-windows-${arch}" },
+                "rules": [{ "action": "allow", "os": { "name": "windows" } }]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn deserialize_libraries() {
+        use crate::version_manifest::VersionManifest;
+        let manifest: VersionManifest = serde_json::from_str(LEGACY_MANIFEST).unwrap();
+        assert_eq!(manifest.libraries.len(), 3);
+
+        let patchy = &manifest.libraries[0];
+        let artifact = patchy.downloads.as_ref().unwrap().artifact.as_ref().unwrap();
+        assert_eq!(artifact.path.as_deref(), Some("com/mojang/patchy/1.1/patchy-1.1.jar"));
+        assert!(patchy.rules.is_none());
+
+        let platform = &manifest.libraries[1];
+        let classifiers = platform.downloads.as_ref().unwrap().classifiers.as_ref().unwrap();
+        assert!(classifiers.contains_key("natives-linux"));
+        assert_eq!(platform.natives.as_ref().unwrap()["windows"], "natives-windows");
+        assert_eq!(platform.extract.as_ref().unwrap().exclude, vec!["META-INF/".to_string()]);
+        assert_eq!(platform.rules.as_ref().unwrap().len(), 2);
+
+        let twitch = &manifest.libraries[2];
+        assert!(twitch.downloads.as_ref().unwrap().artifact.is_none());
+        assert!(twitch.artifact().is_none());
+    }
+
+    #[test]
+    fn library_downloads() {
+        use crate::rules::RuleEnvironment;
+        use crate::version_manifest::VersionManifest;
+        let manifest: VersionManifest = serde_json::from_str(LEGACY_MANIFEST).unwrap();
+        let directory = std::path::Path::new("libraries");
+
+        let linux = manifest.library_downloads(directory, &RuleEnvironment::new("linux", "x86_64"));
+        let paths: Vec<_> = linux.iter().map(|item| item.path.clone()).collect();
+        assert_eq!(
+            paths,
+            [
+                "com/mojang/patchy/1.1/patchy-1.1.jar",
+                "org/lwjgl/lwjgl/lwjgl-platform/2.9.4-nightly-20150209/lwjgl-platform-2.9.4-nightly-20150209.jar",
+                "org/lwjgl/lwjgl/lwjgl-platform/2.9.4-nightly-20150209/lwjgl-platform-2.9.4-nightly-20150209-natives-linux.jar",
+            ]
+            .map(|path| directory.join(path).to_string_lossy().into_owned())
+        );
+        assert_eq!(linux[0].sha1.as_deref(), Some("aef610b34a1be37fa851825f12372b78424d8903"));
+
+        let windows = manifest.library_downloads(directory, &RuleEnvironment::new("windows", "x86"));
+        assert_eq!(windows.len(), 4);
+        assert!(windows[3].path.ends_with("twitch-platform-6.5-natives-windows-32.jar"));
+    }
+
+    #[test]
+    fn merge() {
+        use crate::arguments::ArgumentContext;
+        use crate::rules::RuleEnvironment;
+        use crate::version_manifest::VersionManifest;
+        let parent: VersionManifest = serde_json::from_str(LEGACY_MANIFEST).unwrap();
+        let child: VersionManifest = serde_json::from_str(
+            r#"{
+                "id": "fabric-loader-0.15.11-1.12.2",
+                "inheritsFrom": "1.12.2",
+                "releaseTime": "2024-05-06T19:41:27+0000",
+                "time": "2024-05-06T19:41:27+0000",
+                "type": "release",
+                "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+                "arguments": { "game": [], "jvm": ["-DFabricMcEmu= net.minecraft.client.main.Main "] },
+                "libraries": [
+                    { "name": "net.fabricmc:fabric-loader:0.15.11", "url": "https://maven.fabricmc.net/" },
+                    { "name": "com.mojang:patchy:1.3", "url": "https://maven.fabricmc.net/" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(child.inherits_from.as_deref(), Some("1.12.2"));
+        assert!(child.downloads.is_none());
+
+        let merged = child.merge(parent);
+        assert_eq!(merged.id, "fabric-loader-0.15.11-1.12.2");
+        assert!(merged.inherits_from.is_none());
+        assert_eq!(merged.main_class, "net.fabricmc.loader.impl.launch.knot.KnotClient");
+        assert_eq!(merged.minimal_launcher_version, 18);
+        assert_eq!(merged.assets.as_deref(), Some("1.12"));
+        assert!(merged.downloads.is_some());
+        assert!(merged.logging.is_some());
+
+        let names: Vec<_> = merged.libraries.iter().map(|library| library.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "net.fabricmc:fabric-loader:0.15.11",
+                "com.mojang:patchy:1.3",
+                "org.lwjgl.lwjgl:lwjgl-platform:2.9.4-nightly-20150209",
+                "tv.twitch:twitch-platform:6.5:natives-windows-${arch}",
+            ]
+        );
+
+        let context = ArgumentContext { auth_player_name: "Steve".to_string(), ..Default::default() };
+        let environment = RuleEnvironment::new("linux", "x86_64");
+        let jvm = merged.jvm_arguments(&context, &environment);
+        assert_eq!(jvm.last().map(String::as_str), Some("-DFabricMcEmu= net.minecraft.client.main.Main "));
+        assert_eq!(&merged.game_arguments(&context, &environment)[..2], ["--username", "Steve"]);
+    }
+
+    #[test]
+    fn argument_keys() {
+        use crate::version_manifest::{Arguments, VersionManifest};
+        let manifest: VersionManifest = serde_json::from_str(LEGACY_MANIFEST).unwrap();
+        assert!(matches!(manifest.arguments, Arguments::Pre113(_)));
+
+        let profile = |arguments: &str| {
+            serde_json::from_str::<VersionManifest>(&format!(
+                r#"{{
+                    "id": "1.12.2-forge-14.23.5.2860",
+                    "mainClass": "net.minecraft.launchwrapper.Launch",
+                    "releaseTime": "2017-09-18T08:39:46+00:00",
+                    "time": "2017-09-18T08:39:46+00:00",
+                    "type": "release",
+                    "minecraftArguments": "--username ${{auth_player_name}} --tweakClass net.minecraftforge.fml.common.launcher.FMLTweaker",
+                    "arguments": {}
+                }}"#,
+                arguments
+            ))
+            .unwrap()
+        };
+
+        let Arguments::Pre113(arguments) = profile(r#"{ "game": [], "jvm": ["-Dfml.ignoreInvalidMinecraftCertificates=true"] }"#).arguments else {
+            panic!("Expected the minecraftArguments of a profile without game arguments");
+        };
+        assert!(arguments.ends_with("FMLTweaker"));
+
+        let Arguments::Post113(arguments) = profile(r#"{ "game": ["--username", "${auth_player_name}"], "jvm": [] }"#).arguments else {
+            panic!("Expected the arguments of a profile with game arguments");
+        };
+        assert_eq!(arguments.game.len(), 2);
+    }
+
+    #[test]
+    fn round_trip() {
+        use crate::version_manifest::VersionManifest;
+        let modern = r#"{
+            "id": "1.20.1",
+            "mainClass": "net.minecraft.client.main.Main",
+            "minimumLauncherVersion": 21,
+            "releaseTime": "2023-06-12T13:25:51+00:00",
+            "time": "2023-06-12T13:25:51+00:00",
+            "type": "release",
+            "complianceLevel": 1,
+            "arguments": {
+                "game": ["--username", "${auth_player_name}", { "rules": [{ "action": "allow", "features": { "is_demo_user": true } }], "value": "--demo" }],
+                "jvm": [{ "rules": [{ "action": "allow", "os": { "arch": "x86" } }], "value": "-Xss1M" }, "-cp", "${classpath}"]
+            },
+            "downloads": {
+                "client": { "sha1": "0c3ec587af28e5a785c0b4a7b8a30f9a8f78f838", "size": 23028225, "url": "https://piston-data.mojang.com/v1/objects/0c3ec587af28e5a785c0b4a7b8a30f9a8f78f838/client.jar" },
+                "client_mappings": { "sha1": "6c48521eed01fe2e8ecdadbd5ae348415f3c47da", "size": 7714357, "url": "https://piston-data.mojang.com/v1/objects/6c48521eed01fe2e8ecdadbd5ae348415f3c47da/client.txt" },
+                "server": { "sha1": "84194a2f286ef7c14ed7ce0090dba59902951553", "size": 49150256, "url": "https://piston-data.mojang.com/v1/objects/84194a2f286ef7c14ed7ce0090dba59902951553/server.jar" }
+            },
+            "javaVersion": { "component": "java-runtime-gamma", "majorVersion": 17 },
+            "libraries": [
+                {
+                    "name": "ca.weblite:java-objc-bridge:1.1",
+                    "downloads": {
+                        "artifact": {
+                            "path": "ca/weblite/java-objc-bridge/1.1/java-objc-bridge-1.1.jar",
+                            "sha1": "1227f9e0666314f9de41477e3ec277e542ed7f7b",
+                            "size": 1330045,
+                            "url": "https://libraries.minecraft.net/ca/weblite/java-objc-bridge/1.1/java-objc-bridge-1.1.jar"
+                        }
+                    },
+                    "rules": [{ "action": "allow", "os": { "name": "osx" } }]
+                }
+            ]
+        }"#;
+
+        for json in [LEGACY_MANIFEST, modern] {
+            let expected: serde_json::Value = serde_json::from_str(json).unwrap();
+            let manifest: VersionManifest = serde_json::from_str(json).unwrap();
+            assert_eq!(serde_json::to_value(&manifest).unwrap(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trip_mojang() {
+        use crate::manifest_v2::ManifestV2;
+        use crate::version_manifest::VersionManifest;
+        #[cfg(feature = "log")]
+        setup_logging();
+
+        let manifest = ManifestV2::fetch().await.expect("Failed to fetch versions.");
+        for id in ["1.12.2", "1.20"] {
+            let version = manifest.versions.iter().find(|version| version.id == id).unwrap();
+            let json = reqwest::get(&version.url).await.unwrap().text().await.unwrap();
+            let expected: serde_json::Value = serde_json::from_str(&json).unwrap();
+            let manifest: VersionManifest = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_value(&manifest).unwrap(), expected, "{} changed when written back", id);
+        }
+    }
+
+    #[test]
+    fn logging_argument() {
+        use crate::version_manifest::VersionManifest;
+        let manifest: VersionManifest = serde_json::from_str(LEGACY_MANIFEST).unwrap();
+        let logging = manifest.logging.unwrap();
+        assert_eq!(logging.client.log_type, "log4j2-xml");
+        assert_eq!(logging.client.file.id.as_deref(), Some("client-1.12.xml"));
+
+        let path = std::path::Path::new("assets").join("log_configs").join("client-1.12.xml");
+        assert_eq!(logging.client.jvm_argument(&path), format!("-Dlog4j.configurationFile={}", path.display()));
+    }
+
     #[tokio::test]
     async fn download_server() {
         use crate::manifest_v2::ManifestV2;